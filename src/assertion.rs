@@ -0,0 +1,221 @@
+use regex::Regex;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+use crate::http::HttpResponse;
+use crate::js::{TestResult, is_definite_path};
+use crate::parser::{Assertion, AssertionOp, AssertionSubject};
+
+/// Check a declarative `??` assertion against a response.
+pub fn evaluate(assertion: &Assertion, response: &HttpResponse) -> TestResult {
+    let outcome = actual_value(&assertion.subject, response).and_then(|actual| {
+        let actual = match (assertion.length, actual) {
+            (true, Some(value)) => Some(length_of(&value)?),
+            (_, actual) => actual,
+        };
+        check(assertion, actual.as_ref())
+    });
+
+    match outcome {
+        Ok(()) => TestResult::pass(&assertion.source),
+        Err(message) => TestResult::fail(&assertion.source, message).at_line(assertion.line),
+    }
+}
+
+/// The value the assertion inspects, or `None` if it is absent (a missing
+/// header or a JSONPath with no match).
+fn actual_value(subject: &AssertionSubject, response: &HttpResponse) -> Result<Option<Value>, String> {
+    Ok(match subject {
+        AssertionSubject::Status => Some(Value::from(response.status)),
+        AssertionSubject::Duration => Some(Value::from(response.elapsed_ms as u64)),
+        AssertionSubject::Body => Some(Value::String(response.body_raw.clone())),
+        AssertionSubject::Header(name) => response
+            .headers
+            .get(&name.to_lowercase())
+            .and_then(|values| values.first())
+            .map(|v| Value::String(v.clone())),
+        AssertionSubject::JsonPath(expression) => {
+            let Some(body) = &response.body_json else {
+                return Err("response body is not JSON".to_string());
+            };
+            let path = JsonPath::parse(expression)
+                .map_err(|e| format!("invalid JSONPath '{expression}': {e}"))?;
+            let nodes = path.query(body).all();
+            if is_definite_path(expression) {
+                nodes.first().map(|v| (*v).clone())
+            } else if nodes.is_empty() {
+                None
+            } else {
+                Some(Value::Array(nodes.into_iter().cloned().collect()))
+            }
+        }
+    })
+}
+
+fn length_of(value: &Value) -> Result<Value, String> {
+    match value {
+        Value::String(s) => Ok(Value::from(s.chars().count())),
+        Value::Array(a) => Ok(Value::from(a.len())),
+        Value::Object(o) => Ok(Value::from(o.len())),
+        other => Err(format!("{other} has no length")),
+    }
+}
+
+fn check(assertion: &Assertion, actual: Option<&Value>) -> Result<(), String> {
+    let expected = assertion.expected.as_deref().unwrap_or_default();
+    let Some(actual) = actual else {
+        return match assertion.op {
+            AssertionOp::NotExists => Ok(()),
+            _ => Err(format!("{} does not exist", describe_subject(&assertion.subject))),
+        };
+    };
+
+    let passed = match assertion.op {
+        AssertionOp::Exists => true,
+        AssertionOp::NotExists => false,
+        AssertionOp::Eq => equals(actual, expected),
+        AssertionOp::Ne => !equals(actual, expected),
+        AssertionOp::Lt | AssertionOp::Le | AssertionOp::Gt | AssertionOp::Ge => {
+            let (Some(a), Ok(e)) = (as_number(actual), expected.parse::<f64>()) else {
+                return Err(format!("cannot compare {} with {expected} as numbers", display(actual)));
+            };
+            match assertion.op {
+                AssertionOp::Lt => a < e,
+                AssertionOp::Le => a <= e,
+                AssertionOp::Gt => a > e,
+                _ => a >= e,
+            }
+        }
+        AssertionOp::Contains => contains(actual, expected),
+        AssertionOp::NotContains => !contains(actual, expected),
+        AssertionOp::Matches => {
+            let re = Regex::new(expected).map_err(|e| format!("invalid regex '{expected}': {e}"))?;
+            re.is_match(&display(actual))
+        }
+    };
+
+    if passed {
+        Ok(())
+    } else {
+        Err(format!("expected {}, got {}", assertion.source, display(actual)))
+    }
+}
+
+/// Compare with an expected literal: numerically when both sides are numbers,
+/// otherwise as JSON (`true`, `null`, `[1,2]`, ...) or as text.
+fn equals(actual: &Value, expected: &str) -> bool {
+    if let (Some(a), Ok(e)) = (as_number(actual), expected.parse::<f64>()) {
+        return a == e;
+    }
+    if let Ok(expected_json) = serde_json::from_str::<Value>(expected)
+        && !expected_json.is_string()
+    {
+        return *actual == expected_json;
+    }
+    display(actual) == expected
+}
+
+fn contains(actual: &Value, expected: &str) -> bool {
+    match actual {
+        Value::Array(items) => items.iter().any(|item| equals(item, expected)),
+        Value::Object(map) => map.contains_key(expected),
+        other => display(other).contains(expected),
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Strings without quotes; everything else as JSON.
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn describe_subject(subject: &AssertionSubject) -> String {
+    match subject {
+        AssertionSubject::Status => "status".to_string(),
+        AssertionSubject::Duration => "duration".to_string(),
+        AssertionSubject::Body => "body".to_string(),
+        AssertionSubject::Header(name) => format!("header {name}"),
+        AssertionSubject::JsonPath(expression) => format!("jsonpath {expression}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::http::ContentType;
+    use crate::parser::parse_http_file;
+
+    fn response() -> HttpResponse {
+        let body = r#"{"id": 7, "name": "widget", "items": [{"sku": "a"}, {"sku": "b"}]}"#;
+        HttpResponse {
+            status: 200,
+            headers: HashMap::from([(
+                "content-type".to_string(),
+                vec!["application/json; charset=utf-8".to_string()],
+            )]),
+            body_raw: body.to_string(),
+            body_json: serde_json::from_str(body).ok(),
+            content_type: Some(ContentType {
+                mime_type: "application/json".to_string(),
+                charset: Some("utf-8".to_string()),
+            }),
+            elapsed_ms: 42,
+        }
+    }
+
+    fn run(lines: &str) -> Vec<TestResult> {
+        let content = format!("GET https://example.com\n{lines}\n");
+        let parsed = parse_http_file(&content).unwrap();
+        let resp = response();
+        parsed.requests[0]
+            .assertions
+            .iter()
+            .map(|a| evaluate(a, &resp))
+            .collect()
+    }
+
+    #[test]
+    fn passing_assertions() {
+        let results = run(
+            "?? status == 200\n\
+             ?? header Content-Type contains json\n\
+             ?? jsonpath $.id exists\n\
+             ?? jsonpath $.missing not exists\n\
+             ?? jsonpath $.items length > 1\n\
+             ?? jsonpath $.items[*].sku contains b\n\
+             ?? jsonpath $.name == \"widget\"\n\
+             ?? jsonpath $.name matches ^wid\n\
+             ?? duration < 500\n\
+             ?? body not contains error",
+        );
+        for result in &results {
+            assert!(result.passed, "{result:?}");
+        }
+        assert_eq!(results.len(), 10);
+    }
+
+    #[test]
+    fn failing_assertion_reports_actual_value() {
+        let results = run("?? status == 201\n?? header X-Trace exists");
+        assert_eq!(
+            results[0].failure_message().as_deref(),
+            Some("expected status == 201, got 200")
+        );
+        assert_eq!(
+            results[1].failure_message().as_deref(),
+            Some("header X-Trace does not exist")
+        );
+    }
+}
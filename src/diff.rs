@@ -0,0 +1,172 @@
+/// Above this many line comparisons, the changed part of a diff is shown as
+/// all of its expected lines removed and all of its actual lines added.
+const MAX_DIFF_WORK: usize = 25_000_000;
+
+/// Line diff of `expected` against `actual` (longest common subsequence).
+/// Lines are prefixed with `-` (only in expected), `+` (only in actual) or
+/// left unmarked, and indented for display under a failure message.
+///
+/// The common prefix and suffix are matched directly and the rest with
+/// Hirschberg's algorithm, so memory stays linear in the input size.
+pub fn diff_lines(expected: &str, actual: &str) -> Vec<String> {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_changed = &old[prefix..old.len() - suffix];
+    let new_changed = &new[prefix..new.len() - suffix];
+
+    let mut matches = Vec::new();
+    if old_changed.len().saturating_mul(new_changed.len()) <= MAX_DIFF_WORK {
+        common_lines(old_changed, new_changed, (0, 0), &mut matches);
+    }
+
+    let mut out: Vec<String> = old[..prefix].iter().map(|l| format!("    {l}")).collect();
+    let (mut i, mut j) = (0, 0);
+    let end = (old_changed.len(), new_changed.len());
+    for &(next_i, next_j) in matches.iter().chain([&end]) {
+        out.extend(old_changed[i..next_i].iter().map(|l| format!("  - {l}")));
+        out.extend(new_changed[j..next_j].iter().map(|l| format!("  + {l}")));
+        if let Some(line) = old_changed.get(next_i) {
+            out.push(format!("    {line}"));
+        }
+        (i, j) = (next_i + 1, next_j + 1);
+    }
+    out.extend(old[old.len() - suffix..].iter().map(|l| format!("    {l}")));
+    out
+}
+
+/// Push the positions (offset by `offset`) of a longest common subsequence of
+/// `old` and `new` onto `matches`, in order.
+fn common_lines(old: &[&str], new: &[&str], offset: (usize, usize), matches: &mut Vec<(usize, usize)>) {
+    if old.is_empty() || new.is_empty() {
+        return;
+    }
+    if let [line] = old {
+        if let Some(k) = new.iter().position(|l| l == line) {
+            matches.push((offset.0, offset.1 + k));
+        }
+        return;
+    }
+
+    // Split `new` where the LCS of the two halves of `old` meet
+    let mid = old.len() / 2;
+    let forward = lcs_lengths(old[..mid].iter(), new.iter());
+    let backward = lcs_lengths(old[mid..].iter().rev(), new.iter().rev());
+    let split = (0..=new.len())
+        .max_by_key(|&k| (forward[k] + backward[new.len() - k], std::cmp::Reverse(k)))
+        .unwrap_or(0);
+
+    common_lines(&old[..mid], &new[..split], offset, matches);
+    common_lines(&old[mid..], &new[split..], (offset.0 + mid, offset.1 + split), matches);
+}
+
+/// Length of the LCS of `old` and each prefix of `new`: entry `k` is for the
+/// first `k` lines.
+fn lcs_lengths<'a>(
+    old: impl Iterator<Item = &'a &'a str>,
+    new: impl Iterator<Item = &'a &'a str> + Clone,
+) -> Vec<usize> {
+    let mut row = vec![0; new.clone().count() + 1];
+    for line in old {
+        // row[k - 1] from the previous line
+        let mut diagonal = 0;
+        for (k, other) in new.clone().enumerate() {
+            let above = row[k + 1];
+            row[k + 1] = if line == other { diagonal + 1 } else { above.max(row[k]) };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+/// Replace unchanged lines more than `context` lines away from any change in
+/// a diff from [`diff_lines`] with a `...` marker.
+pub fn collapse_unchanged(lines: Vec<String>, context: usize) -> Vec<String> {
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.starts_with("  -") || l.starts_with("  +"))
+        .map(|(i, _)| i)
+        .collect();
+    let near_change = |i: usize| changed.iter().any(|&c| c.abs_diff(i) <= context);
+
+    let mut out = Vec::new();
+    let mut hidden = Vec::new();
+    let flush = |hidden: &mut Vec<String>, out: &mut Vec<String>| {
+        // A marker in place of a single line would hide nothing
+        if hidden.len() == 1 {
+            out.append(hidden);
+        } else if !hidden.is_empty() {
+            out.push("    ...".to_string());
+            hidden.clear();
+        }
+    };
+    for (i, line) in lines.into_iter().enumerate() {
+        if near_change(i) {
+            flush(&mut hidden, &mut out);
+            out.push(line);
+        } else {
+            hidden.push(line);
+        }
+    }
+    flush(&mut hidden, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collapse_unchanged, diff_lines};
+
+    #[test]
+    fn diffs_changed_lines() {
+        let expected = "{\n  \"id\": 2,\n  \"name\": \"a\"\n}";
+        let actual = "{\n  \"id\": 1,\n  \"name\": \"a\"\n}";
+        assert_eq!(
+            diff_lines(expected, actual),
+            vec![
+                "    {",
+                "  -   \"id\": 2,",
+                "  +   \"id\": 1,",
+                "      \"name\": \"a\"",
+                "    }",
+            ]
+        );
+    }
+
+    #[test]
+    fn diffs_long_inputs() {
+        let expected: Vec<String> = (0..30_000).map(|i| format!("line {i}")).collect();
+        let mut actual = expected.clone();
+        actual[15_000] = "changed".to_string();
+        actual.insert(15_200, "added".to_string());
+        actual.remove(15_501);
+        let lines = diff_lines(&expected.join("\n"), &actual.join("\n"));
+
+        let changes: Vec<&String> = lines.iter().filter(|l| !l.starts_with("    ")).collect();
+        assert_eq!(changes, ["  - line 15000", "  + changed", "  + added", "  - line 15500"]);
+        assert_eq!(lines.len(), 30_002);
+
+        // Too many lines differ to look for common ones
+        let replaced: Vec<String> = (0..6_000).map(|i| format!("other {i}")).collect();
+        let lines = diff_lines(&expected[..6_000].join("\n"), &replaced.join("\n"));
+        assert_eq!(lines.len(), 12_000);
+        assert!(lines[..6_000].iter().all(|l| l.starts_with("  - ")));
+    }
+
+    #[test]
+    fn collapses_distant_unchanged_lines() {
+        let expected = "a\nb\nc\nd\ne\nf\ng\nh";
+        let actual = "a\nb\nc\nd\ne\nF\ng\nh";
+        assert_eq!(
+            collapse_unchanged(diff_lines(expected, actual), 1),
+            vec!["    ...", "    e", "  - f", "  + F", "    g", "    h"]
+        );
+    }
+}
@@ -90,8 +90,7 @@ fn private_env_path(env_file: &Path) -> std::path::PathBuf {
 
     // http-client.env.json -> http-client.private.env.json
     // Handle double extension: if stem ends with ".env", insert ".private" before ".env"
-    let private_name = if stem.ends_with(".env") {
-        let base = &stem[..stem.len() - 4];
+    let private_name = if let Some(base) = stem.strip_suffix(".env") {
         format!("{}.private.env.{}", base, ext)
     } else {
         format!("{}.private.{}", stem, ext)
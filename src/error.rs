@@ -5,6 +5,11 @@ pub enum AppError {
     #[error("Parse error at line {line}: {message}")]
     Parse { line: usize, message: String },
 
+    /// A command-line value that can't be applied, such as an `--index`
+    /// past the end of a file.
+    #[error("Usage error: {0}")]
+    Usage(String),
+
     #[error("Environment error: {0}")]
     Environment(String),
 
@@ -18,9 +23,35 @@ pub enum AppError {
     #[error("JavaScript error: {0}")]
     JavaScript(String),
 
+    /// An error thrown by a response handler, with the `.http` line it was
+    /// thrown at when that is known.
+    #[error("JavaScript error: {message}")]
+    HandlerException { message: String, line: Option<usize> },
+
+    #[error("Limit exceeded in request '{request}' (handler line {line}): {message}")]
+    HandlerLimit {
+        request: String,
+        line: usize,
+        message: String,
+    },
+
+    #[error("Schema error: {0}")]
+    Schema(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 }
+
+impl AppError {
+    /// Line in the `.http` file a handler error points at, when known.
+    pub fn handler_line(&self) -> Option<usize> {
+        match self {
+            AppError::HandlerException { line, .. } => *line,
+            AppError::HandlerLimit { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+/// Expand the paths given on the command line into the `.http` files to run,
+/// in order. Existing files are taken as given; directories are searched
+/// recursively and glob patterns (`*`, `?`, `[...]`, and `**` for any number
+/// of directories) are matched, both picking up only `.http` files, in sorted
+/// order. A file named more than once, in whatever form, runs once, at its
+/// first position.
+pub fn expand(inputs: &[String]) -> Result<Vec<PathBuf>, AppError> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut seen = HashSet::new();
+    for input in inputs {
+        let path = PathBuf::from(input);
+        let found = if path.is_dir() {
+            let mut found = Vec::new();
+            walk(&path, &mut found)?;
+            found.retain(|p| is_http_file(p));
+            found.sort();
+            found
+        } else if !path.exists() && is_pattern(input) {
+            glob(input)?
+        } else {
+            vec![path]
+        };
+        if found.is_empty() {
+            return Err(AppError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{input}: no .http files found"),
+            )));
+        }
+        for file in found {
+            // `./a.http` and `a.http` are the same file
+            let key = std::fs::canonicalize(&file).unwrap_or_else(|_| file.clone());
+            if seen.insert(key) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn is_pattern(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+fn is_http_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("http"))
+}
+
+/// Every file under `dir`, skipping hidden entries. Symlinked directories
+/// are not followed.
+fn walk(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), AppError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        AppError::Io(io::Error::new(e.kind(), format!("{}: {}", dir.display(), e)))
+    })?;
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            walk(&path, out)?;
+        } else if path.is_file() {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The `.http` files matching a glob pattern, skipping hidden entries unless
+/// the pattern names them.
+fn glob(pattern: &str) -> Result<Vec<PathBuf>, AppError> {
+    let options = glob::MatchOptions {
+        require_literal_leading_dot: true,
+        ..Default::default()
+    };
+    let paths = glob::glob_with(pattern, options)
+        .map_err(|e| AppError::Usage(format!("invalid pattern '{pattern}': {e}")))?;
+    let mut matches = Vec::new();
+    for path in paths {
+        let path = path.map_err(|e| {
+            AppError::Io(io::Error::new(
+                e.error().kind(),
+                format!("{}: {}", e.path().display(), e.error()),
+            ))
+        })?;
+        if is_http_file(&path) && path.is_file() {
+            matches.push(path);
+        }
+    }
+    matches.sort();
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_directories_and_globs_in_order() {
+        let dir = std::env::temp_dir().join(format!("httprun-files-{}", std::process::id()));
+        for file in ["b.http", "a.http", "notes.md", "users/list.http", "users/deep/get.http", ".hidden/x.http"] {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "GET https://example.com\n").unwrap();
+        }
+        let root = dir.display().to_string();
+
+        let all = expand(std::slice::from_ref(&root)).unwrap();
+        let names: Vec<_> = all.iter().map(|p| p.strip_prefix(&dir).unwrap().to_path_buf()).collect();
+        assert_eq!(
+            names,
+            ["a.http", "b.http", "users/deep/get.http", "users/list.http"].map(PathBuf::from)
+        );
+
+        let top = expand(&[format!("{root}/*.http")]).unwrap();
+        assert_eq!(top, [dir.join("a.http"), dir.join("b.http")]);
+
+        let nested = expand(&[format!("{root}/users/**/*.http"), format!("{root}/a.http")]).unwrap();
+        assert_eq!(
+            nested,
+            [dir.join("users/deep/get.http"), dir.join("users/list.http"), dir.join("a.http")]
+        );
+
+        let deduped = expand(&[format!("{root}/b.http"), format!("{root}/[ab].http")]).unwrap();
+        assert_eq!(deduped, [dir.join("b.http"), dir.join("a.http")]);
+
+        assert!(expand(&[format!("{root}/*.rest")]).is_err());
+        assert!(expand(&[format!("{root}/**/*.http")]).unwrap().iter().all(|p| !p.starts_with(dir.join(".hidden"))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn takes_existing_paths_literally_and_runs_each_file_once() {
+        let dir = std::env::temp_dir().join(format!("httprun-files-literal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["users [v2].http", "a.http", "u.http"] {
+            std::fs::write(dir.join(file), "GET https://example.com\n").unwrap();
+        }
+        let root = dir.display().to_string();
+
+        // `[v2]` would otherwise match a single `v` or `2`
+        let literal = format!("{root}/users [v2].http");
+        assert_eq!(expand(std::slice::from_ref(&literal)).unwrap(), [PathBuf::from(&literal)]);
+
+        let same = expand(&[
+            format!("{root}/a.http"),
+            format!("{root}/./a.http"),
+            format!("{root}/../{}/a.http", dir.file_name().unwrap().to_string_lossy()),
+            format!("{root}/*.http"),
+        ])
+        .unwrap();
+        assert_eq!(same, [dir.join("a.http"), dir.join("u.http"), dir.join("users [v2].http")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
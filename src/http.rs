@@ -1,11 +1,11 @@
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use reqwest::blocking::Client;
+pub use reqwest::blocking::Client;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 
 use crate::error::AppError;
-use crate::parser::{HttpMethod, ParsedRequest};
+use crate::parser::{Header, HttpMethod, ParsedRequest};
 
 #[derive(Debug, Clone)]
 pub struct ContentType {
@@ -23,10 +23,39 @@ pub struct HttpResponse {
     pub elapsed_ms: u128,
 }
 
-pub fn execute_request(request: &ParsedRequest) -> Result<HttpResponse, AppError> {
-    let client = Client::new();
+pub fn execute_request(client: &Client, request: &ParsedRequest) -> Result<HttpResponse, AppError> {
+    let headers = header_map(&request.headers).map_err(|message| AppError::Parse {
+        line: request.line_number,
+        message,
+    })?;
+    send(client, &request.method, &request.url, headers, request.body.clone(), None)
+}
 
-    let method = match &request.method {
+/// Build a header map, rejecting invalid names and values.
+pub fn header_map(headers: &[Header]) -> Result<HeaderMap, String> {
+    let mut header_map = HeaderMap::new();
+    for h in headers {
+        let name = HeaderName::from_bytes(h.name.as_bytes())
+            .map_err(|e| format!("Invalid header name '{}': {}", h.name, e))?;
+        let value = HeaderValue::from_str(&h.value)
+            .map_err(|e| format!("Invalid header value '{}': {}", h.value, e))?;
+        header_map.insert(name, value);
+    }
+    Ok(header_map)
+}
+
+/// Send a request and collect the response. Used both for requests in the
+/// `.http` file and for `client.fetch` calls made by handlers. Reusing
+/// `client` reuses its pooled connections; `timeout` overrides the client's.
+pub fn send(
+    client: &Client,
+    method: &HttpMethod,
+    url: &str,
+    headers: HeaderMap,
+    body: Option<String>,
+    timeout: Option<Duration>,
+) -> Result<HttpResponse, AppError> {
+    let method = match method {
         HttpMethod::Get => reqwest::Method::GET,
         HttpMethod::Post => reqwest::Method::POST,
         HttpMethod::Put => reqwest::Method::PUT,
@@ -36,25 +65,13 @@ pub fn execute_request(request: &ParsedRequest) -> Result<HttpResponse, AppError
         HttpMethod::Options => reqwest::Method::OPTIONS,
     };
 
-    let mut header_map = HeaderMap::new();
-    for h in &request.headers {
-        let name = HeaderName::from_bytes(h.name.as_bytes())
-            .map_err(|e| AppError::Parse {
-                line: request.line_number,
-                message: format!("Invalid header name '{}': {}", h.name, e),
-            })?;
-        let value = HeaderValue::from_str(&h.value)
-            .map_err(|e| AppError::Parse {
-                line: request.line_number,
-                message: format!("Invalid header value '{}': {}", h.value, e),
-            })?;
-        header_map.insert(name, value);
-    }
+    let mut builder = client.request(method, url).headers(headers);
 
-    let mut builder = client.request(method, &request.url).headers(header_map);
-
-    if let Some(body) = &request.body {
-        builder = builder.body(body.clone());
+    if let Some(body) = body {
+        builder = builder.body(body);
+    }
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
     }
 
     let start = Instant::now();
@@ -102,3 +119,88 @@ pub fn execute_request(request: &ParsedRequest) -> Result<HttpResponse, AppError
         elapsed_ms,
     })
 }
+
+/// Prefix `https://` to a URL written without a scheme.
+pub fn ensure_http_scheme(url: &str) -> String {
+    let trimmed = url.trim();
+    if has_url_scheme(trimmed) {
+        trimmed.to_string()
+    } else {
+        format!("https://{}", trimmed)
+    }
+}
+
+fn has_url_scheme(url: &str) -> bool {
+    let Some(idx) = url.find("://") else {
+        return false;
+    };
+    if idx == 0 {
+        return false;
+    }
+    let scheme = &url[..idx];
+    let mut chars = scheme.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    if !first.is_ascii_alphabetic() {
+        return false;
+    }
+    let mut has_plus_or_dash = false;
+    let mut has_dot = false;
+    for c in chars {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => {}
+            '+' | '-' => {
+                has_plus_or_dash = true;
+            }
+            '.' => {
+                has_dot = true;
+            }
+            _ => return false,
+        }
+    }
+    // Heuristic: treat dotted, domain-like prefixes without + or - as missing schemes.
+    !has_dot || has_plus_or_dash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ensure_http_scheme, has_url_scheme};
+
+    #[test]
+    fn has_url_scheme_accepts_valid_schemes() {
+        assert!(has_url_scheme("http://example.com"));
+        assert!(has_url_scheme("https://example.com"));
+        assert!(has_url_scheme("ftp://example.com"));
+        assert!(has_url_scheme("custom+v1.2-scheme://example.com"));
+    }
+
+    #[test]
+    fn has_url_scheme_rejects_invalid_or_missing_schemes() {
+        assert!(!has_url_scheme("://example.com"));
+        assert!(!has_url_scheme("1http://example.com"));
+        assert!(!has_url_scheme("http:/example.com"));
+        assert!(!has_url_scheme("example.com/path"));
+        assert!(!has_url_scheme("example.com://path"));
+    }
+
+    #[test]
+    fn ensure_http_scheme_only_prepends_when_missing() {
+        assert_eq!(
+            ensure_http_scheme("https://example.com"),
+            "https://example.com"
+        );
+        assert_eq!(
+            ensure_http_scheme("ftp://example.com"),
+            "ftp://example.com"
+        );
+        assert_eq!(
+            ensure_http_scheme("example.com/path"),
+            "https://example.com/path"
+        );
+        assert_eq!(
+            ensure_http_scheme("  example.com  "),
+            "https://example.com"
+        );
+    }
+}
@@ -1,22 +1,208 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Instant;
 
 use boa_engine::{
-    Context, JsResult, JsValue, NativeFunction,
+    Context, JsError, JsNativeError, JsObject, JsResult, JsValue, NativeFunction,
     js_string,
     object::ObjectInitializer,
+    object::builtins::{JsFunction, JsPromise},
     property::Attribute,
 };
 
-use crate::js::runtime::TestResult;
+use crate::http;
+use crate::js::console::format_args;
+use crate::js::expect::{assertion_diff, is_assertion_error};
+use crate::js::response::build_response_object;
+use crate::js::runtime::{
+    AssertionRecord, FlowControl, HandlerDeadline, LogLevel, LogLine, TestResult, exit_signal,
+    is_exit_signal, is_runtime_limit,
+};
+use crate::js::source_map::HandlerSourceMap;
+use crate::parser::{Header, HttpMethod};
+use crate::schema;
+use crate::snapshot::{self, SnapshotSettings};
+use crate::variable::VariableStore;
+
+/// Name of the test that collects assertions made outside any `client.test`.
+pub const IMPLICIT_TEST_NAME: &str = "Handler assertions";
+
+/// A `client.test` (or the implicit test) that is still collecting assertions.
+#[derive(Debug)]
+struct RunningTest {
+    id: usize,
+    name: String,
+    started: Instant,
+    assertions: Vec<AssertionRecord>,
+    /// The test context that was active when this test started.
+    outer: Option<usize>,
+}
+
+impl RunningTest {
+    fn new(id: usize, name: String, outer: Option<usize>) -> Self {
+        Self {
+            id,
+            name,
+            started: Instant::now(),
+            assertions: Vec::new(),
+            outer,
+        }
+    }
+
+    fn finish(self) -> TestResult {
+        TestResult {
+            name: self.name,
+            passed: self.assertions.iter().all(|a| a.passed),
+            assertions: self.assertions,
+            duration: self.started.elapsed(),
+        }
+    }
+}
+
+/// Tracks the lifecycle of tests in one handler: each `client.test` starts,
+/// collects assertions and ends; assertions made outside any test go to an
+/// implicit test that ends with the handler. A test whose callback returns a
+/// promise is suspended until the promise settles.
+///
+/// While a test's callback runs, its id is the active test context. The event
+/// loop hands the context on to the promise jobs and timers scheduled from
+/// there, so an async test's continuations keep assigning to it.
+#[derive(Debug, Default)]
+pub struct TestTracker {
+    results: Vec<TestResult>,
+    /// Running `client.test`s, innermost last.
+    running: Vec<RunningTest>,
+    /// Async tests waiting for their promise.
+    pending: Vec<RunningTest>,
+    next_id: usize,
+    implicit: Option<RunningTest>,
+    /// The test whose code is running, shared with the event loop.
+    context: Rc<Cell<Option<usize>>>,
+}
+
+impl TestTracker {
+    /// A tracker that reads and sets the active test context through `context`.
+    pub fn with_context(context: Rc<Cell<Option<usize>>>) -> Self {
+        Self {
+            context,
+            ..Default::default()
+        }
+    }
+
+    pub fn start_test(&mut self, name: String) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let outer = self.context.replace(Some(id));
+        self.running.push(RunningTest::new(id, name, outer));
+    }
+
+    /// Record an assertion against the innermost running test, then the async
+    /// test whose continuation is running, then the implicit test.
+    pub fn record_assertion(&mut self, assertion: AssertionRecord) {
+        let context = self.context.get();
+        let test = if let Some(test) = self.running.last_mut() {
+            test
+        } else if let Some(test) = self.pending.iter_mut().find(|t| Some(t.id) == context) {
+            test
+        } else {
+            self.implicit
+                .get_or_insert_with(|| RunningTest::new(usize::MAX, IMPLICIT_TEST_NAME.to_string(), None))
+        };
+        test.assertions.push(assertion);
+    }
+
+    pub fn end_test(&mut self) {
+        if let Some(test) = self.pop_running() {
+            self.results.push(test.finish());
+        }
+    }
+
+    /// Drop the innermost running test without a result (the handler is unwinding).
+    pub fn abandon_test(&mut self) {
+        self.pop_running();
+    }
+
+    /// Move the innermost running test aside until its promise settles.
+    pub fn suspend_test(&mut self) -> Option<usize> {
+        let test = self.pop_running()?;
+        let id = test.id;
+        self.pending.push(test);
+        Some(id)
+    }
+
+    /// End a suspended test, failing it with `failure` if its promise rejected.
+    pub fn settle_test(&mut self, id: usize, failure: Option<AssertionRecord>) {
+        let Some(index) = self.pending.iter().position(|t| t.id == id) else {
+            return;
+        };
+        let mut test = self.pending.remove(index);
+        test.assertions.extend(failure);
+        self.results.push(test.finish());
+    }
+
+    /// Whether an async test is still waiting for its promise.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Close the implicit test and return every result, in the order the tests
+    /// ended. Async tests whose promise never settled are reported as failed.
+    pub fn finish(&mut self) -> Vec<TestResult> {
+        for mut test in std::mem::take(&mut self.pending) {
+            test.assertions.push(AssertionRecord {
+                passed: false,
+                message: "test did not finish: its promise never settled".to_string(),
+                line: None,
+            });
+            self.results.push(test.finish());
+        }
+        if let Some(test) = self.implicit.take() {
+            self.results.push(test.finish());
+        }
+        std::mem::take(&mut self.results)
+    }
+
+    /// Pop the innermost running test and restore the context it replaced.
+    fn pop_running(&mut self) -> Option<RunningTest> {
+        let test = self.running.pop()?;
+        self.context.set(test.outer);
+        Some(test)
+    }
+}
 
 /// Shared state between Rust and JS for the `client` object.
 #[derive(Debug, Default)]
 pub struct JsSharedState {
     pub global_vars: HashMap<String, String>,
-    pub test_results: Vec<TestResult>,
-    pub log_output: Vec<String>,
+    /// Globals removed via `client.global.clear`/`clearAll` that must also be
+    /// removed from the run's variable store.
+    pub removed_globals: HashSet<String>,
+    pub tests: TestTracker,
+    pub log_output: Vec<LogLine>,
+    pub flow: FlowControl,
+    pub source_map: HandlerSourceMap,
+    /// Directory of the `.http` file; `client.validateSchema` paths resolve against it.
+    pub base_dir: PathBuf,
+    pub snapshots: SnapshotSettings,
+    /// The response body as recorded by `client.snapshot`.
+    pub response_body: serde_json::Value,
+    /// Settles when the last async `client.test` has ended; later tests queue behind it.
+    pub async_tests: Option<JsPromise>,
+    /// When the handler has to stop; `client.fetch` won't wait past it.
+    pub deadline: Option<HandlerDeadline>,
+    /// Environment and in-place variables for `client.fetch`; its globals are
+    /// replaced by `global_vars` when substituting.
+    pub variables: VariableStore,
+    /// The client `client.fetch` sends with.
+    pub http_client: Option<http::Client>,
+    /// The handler is an ES module, which boa can't unwind with an
+    /// uncatchable error.
+    pub in_module: bool,
+    /// The `client.exit()` or runtime-limit error stopping a module handler,
+    /// reported in place of the plain error it was thrown as.
+    pub unwinding: Option<JsError>,
 }
 
 /// Build the `client` JS global object.
@@ -34,7 +220,7 @@ pub fn build_client_object(
     let test_fn = unsafe {
         NativeFunction::from_closure(move |_this, args, ctx| {
             let name = args
-                .get(0)
+                .first()
                 .cloned()
                 .unwrap_or(JsValue::undefined())
                 .to_string(ctx)?
@@ -45,34 +231,20 @@ pub fn build_client_object(
                 .cloned()
                 .unwrap_or(JsValue::undefined());
 
-            if let Some(cb) = callback.as_callable() {
-                let pre_len = shared_test.borrow().test_results.len();
-
-                match cb.call(&JsValue::undefined(), &[], ctx) {
-                    Ok(_) => {
-                        let state = shared_test.borrow();
-                        let had_failure = state.test_results.iter().skip(pre_len).any(|r| !r.passed);
-
-                        if !had_failure {
-                            drop(state);
-                            shared_test.borrow_mut().test_results.push(TestResult {
-                                name,
-                                passed: true,
-                                failure_message: None,
-                            });
-                        }
-                    }
-                    Err(e) => {
-                        shared_test.borrow_mut().test_results.push(TestResult {
-                            name,
-                            passed: false,
-                            failure_message: Some(format!("Exception: {e}")),
-                        });
-                    }
-                }
-            }
+            let Some(cb) = callback.as_callable() else {
+                return Ok(JsValue::undefined());
+            };
 
-            Ok(JsValue::undefined())
+            // Once a test is async, later tests wait for it so assertions made
+            // after an `await` are attributed to the right test.
+            let queued = shared_test.borrow().async_tests.clone();
+            let Some(queued) = queued else {
+                return run_test(&shared_test, name, &cb, ctx);
+            };
+            let run = deferred_test_function(Rc::clone(&shared_test), name, cb, ctx);
+            let next = queued.then(Some(run.clone()), Some(run), ctx);
+            shared_test.borrow_mut().async_tests = Some(next.clone());
+            Ok(next.into())
         })
     };
 
@@ -82,7 +254,7 @@ pub fn build_client_object(
     let assert_fn = unsafe {
         NativeFunction::from_closure(move |_this, args, ctx| {
             let condition = args
-                .get(0)
+                .first()
                 .cloned()
                 .unwrap_or(JsValue::from(false))
                 .to_boolean();
@@ -94,13 +266,12 @@ pub fn build_client_object(
                 .to_string(ctx)?
                 .to_std_string_escaped();
 
-            if !condition {
-                shared_assert.borrow_mut().test_results.push(TestResult {
-                    name: message.clone(),
-                    passed: false,
-                    failure_message: Some(message),
-                });
-            }
+            let line = shared_assert.borrow().source_map.call_site_line(ctx);
+            shared_assert.borrow_mut().tests.record_assertion(AssertionRecord {
+                passed: condition,
+                message,
+                line,
+            });
 
             Ok(JsValue::undefined())
         })
@@ -111,27 +282,382 @@ pub fn build_client_object(
     // SAFETY: Same as above.
     let log_fn = unsafe {
         NativeFunction::from_closure(move |_this, args, ctx| {
-            let mut parts = Vec::new();
-            for arg in args.iter() {
-                let s = arg.to_string(ctx)?.to_std_string_escaped();
-                parts.push(s);
-            }
-            let line = parts.join(" ");
-            shared_log.borrow_mut().log_output.push(line);
+            let message = format_args(args, ctx)?;
+            shared_log.borrow_mut().log_output.push(LogLine {
+                level: LogLevel::Log,
+                message,
+            });
+            Ok(JsValue::undefined())
+        })
+    };
+
+    // client.exit() — stop the handler and the rest of the run
+    let shared_exit = Rc::clone(&shared);
+    // SAFETY: Same as above.
+    let exit_fn = unsafe {
+        NativeFunction::from_closure(move |_this, _args, _ctx| {
+            shared_exit.borrow_mut().flow.exit = true;
+            Err(unwind(&shared_exit, exit_signal()))
+        })
+    };
+
+    // client.skip(name) — skip the next request with this name
+    let shared_skip = Rc::clone(&shared);
+    // SAFETY: Same as above.
+    let skip_fn = unsafe {
+        NativeFunction::from_closure(move |_this, args, ctx| {
+            let name = args
+                .first()
+                .cloned()
+                .unwrap_or(JsValue::undefined())
+                .to_string(ctx)?
+                .to_std_string_escaped();
+
+            shared_skip.borrow_mut().flow.skip.push(name);
             Ok(JsValue::undefined())
         })
     };
 
+    // client.jump(name) — continue the run at the named request
+    let shared_jump = Rc::clone(&shared);
+    // SAFETY: Same as above.
+    let jump_fn = unsafe {
+        NativeFunction::from_closure(move |_this, args, ctx| {
+            let name = args
+                .first()
+                .cloned()
+                .unwrap_or(JsValue::undefined())
+                .to_string(ctx)?
+                .to_std_string_escaped();
+
+            shared_jump.borrow_mut().flow.jump_to = Some(name);
+            Ok(JsValue::undefined())
+        })
+    };
+
+    // client.validateSchema(value, path) — check a value against a JSON Schema file
+    let shared_schema = Rc::clone(&shared);
+    // SAFETY: Same as above.
+    let validate_schema_fn = unsafe {
+        NativeFunction::from_closure(move |_this, args, ctx| {
+            let value = args
+                .first()
+                .cloned()
+                .unwrap_or(JsValue::undefined())
+                .to_json(ctx)?
+                .unwrap_or(serde_json::Value::Null);
+
+            let schema_ref = args
+                .get(1)
+                .cloned()
+                .unwrap_or(JsValue::undefined())
+                .to_string(ctx)?
+                .to_std_string_escaped();
+
+            let path = shared_schema.borrow().base_dir.join(&schema_ref);
+            let results = schema::to_test_results(&schema_ref, schema::validate_file(&value, &path));
+            let valid = results.iter().all(|r| r.passed);
+
+            let mut state = shared_schema.borrow_mut();
+            let line = state.source_map.call_site_line(ctx);
+            for result in results {
+                state.tests.record_assertion(assertion_record(&result, line));
+            }
+
+            Ok(JsValue::from(valid))
+        })
+    };
+
+    // client.snapshot(name, {ignore, value}) — compare with a stored snapshot
+    let shared_snapshot = Rc::clone(&shared);
+    // SAFETY: Same as above.
+    let snapshot_fn = unsafe {
+        NativeFunction::from_closure(move |_this, args, ctx| {
+            let name = args
+                .first()
+                .cloned()
+                .unwrap_or(JsValue::undefined())
+                .to_string(ctx)?
+                .to_std_string_escaped();
+
+            let mut ignore = Vec::new();
+            let mut value = None;
+            if let Some(options) = args.get(1).and_then(JsValue::as_object) {
+                let ignore_val = options.get(js_string!("ignore"), ctx)?;
+                if let Some(serde_json::Value::Array(paths)) = ignore_val.to_json(ctx)? {
+                    ignore = paths
+                        .iter()
+                        .filter_map(|p| p.as_str().map(str::to_string))
+                        .collect();
+                }
+                if options.has_property(js_string!("value"), ctx)? {
+                    value = options.get(js_string!("value"), ctx)?.to_json(ctx)?;
+                }
+            }
+
+            let mut state = shared_snapshot.borrow_mut();
+            let value = value.unwrap_or_else(|| state.response_body.clone());
+            let result = snapshot::check(&state.snapshots, &name, &value, &ignore);
+            let passed = result.passed;
+            let line = state.source_map.call_site_line(ctx);
+            state.tests.record_assertion(assertion_record(&result, line));
+
+            Ok(JsValue::from(passed))
+        })
+    };
+
+    // client.fetch(method, url, options) — send a request from the handler
+    let shared_fetch = Rc::clone(&shared);
+    // SAFETY: Same as above.
+    let fetch_fn = unsafe {
+        NativeFunction::from_closure(move |_this, args, ctx| fetch(&shared_fetch, args, ctx))
+    };
+
     let client = ObjectInitializer::new(context)
         .property(js_string!("global"), global_obj, Attribute::READONLY)
         .function(test_fn, js_string!("test"), 2)
         .function(assert_fn, js_string!("assert"), 2)
         .function(log_fn, js_string!("log"), 1)
+        .function(validate_schema_fn, js_string!("validateSchema"), 2)
+        .function(snapshot_fn, js_string!("snapshot"), 2)
+        .function(fetch_fn, js_string!("fetch"), 3)
+        .function(exit_fn, js_string!("exit"), 0)
+        .function(skip_fn, js_string!("skip"), 1)
+        .function(jump_fn, js_string!("jump"), 1)
         .build();
 
     Ok(client.into())
 }
 
+/// `client.fetch(method, url, {headers, body})` — send a request synchronously
+/// and return a response object shaped like `response`. Object bodies are sent
+/// as JSON. The request is cut off at the handler's deadline.
+fn fetch(
+    shared: &Rc<RefCell<JsSharedState>>,
+    args: &[JsValue],
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let method_name = args
+        .first()
+        .cloned()
+        .unwrap_or(JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_escaped()
+        .to_uppercase();
+    let method = HttpMethod::from_str(&method_name).ok_or_else(|| {
+        JsNativeError::typ().with_message(format!("client.fetch: unsupported method '{method_name}'"))
+    })?;
+
+    let url = args
+        .get(1)
+        .cloned()
+        .unwrap_or(JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_escaped();
+
+    let mut headers = Vec::new();
+    let mut body = None;
+    if let Some(options) = args.get(2).and_then(JsValue::as_object) {
+        if let Some(header_obj) = options.get(js_string!("headers"), ctx)?.as_object() {
+            for key in header_obj.own_property_keys(ctx)? {
+                let value = header_obj.get(key.clone(), ctx)?;
+                headers.push(Header {
+                    name: key.to_string(),
+                    value: value.to_string(ctx)?.to_std_string_escaped(),
+                });
+            }
+        }
+
+        let body_val = options.get(js_string!("body"), ctx)?;
+        if let Some(text) = body_val.as_string() {
+            body = Some(text.to_std_string_escaped());
+        } else if !body_val.is_null_or_undefined() {
+            let json = body_val.to_json(ctx)?.unwrap_or(serde_json::Value::Null);
+            body = Some(json.to_string());
+            if !headers.iter().any(|h| h.name.eq_ignore_ascii_case("content-type")) {
+                headers.push(Header {
+                    name: "Content-Type".to_string(),
+                    value: "application/json".to_string(),
+                });
+            }
+        }
+    }
+
+    // Resolve `{{variables}}` and a missing scheme like a request in the file
+    let (url, client) = {
+        let state = shared.borrow();
+        let variables = state.variables.with_globals(state.global_vars.clone());
+        let substitute = |text: &str| {
+            variables
+                .substitute(text)
+                .map_err(|e| JsNativeError::error().with_message(format!("client.fetch: {e}")))
+        };
+        for header in &mut headers {
+            header.value = substitute(&header.value)?;
+        }
+        if let Some(text) = &body {
+            body = Some(substitute(text)?);
+        }
+        let url = http::ensure_http_scheme(&substitute(&url)?);
+        (url, state.http_client.clone().unwrap_or_default())
+    };
+
+    let header_map = http::header_map(&headers)
+        .map_err(|e| JsNativeError::typ().with_message(format!("client.fetch: {e}")))?;
+    let deadline = shared.borrow().deadline.clone();
+    let timeout = match deadline.map(|d| d.remaining()) {
+        Some(None) => {
+            return Err(JsNativeError::runtime_limit()
+                .with_message("client.fetch: the handler is out of time")
+                .into());
+        }
+        Some(remaining) => remaining,
+        None => None,
+    };
+    let response = http::send(&client, &method, &url, header_map, body, timeout).map_err(|e| {
+        JsNativeError::error().with_message(format!("client.fetch {method_name} {url}: {e}"))
+    })?;
+    build_response_object(&response, ctx)
+}
+
+/// Run one `client.test` body. A callback that returns a promise suspends the
+/// test until it settles; the returned promise resolves once the test has ended.
+fn run_test(
+    shared: &Rc<RefCell<JsSharedState>>,
+    name: String,
+    callback: &JsObject,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    shared.borrow_mut().tests.start_test(name);
+
+    let outcome = match callback.call(&JsValue::undefined(), &[], ctx) {
+        Ok(value) => value,
+        Err(e) => {
+            // client.exit() and runtime limits unwind the whole handler
+            if is_exit_signal(&e) || is_runtime_limit(&e) || shared.borrow().unwinding.is_some() {
+                shared.borrow_mut().tests.abandon_test();
+                return Err(unwind(shared, e));
+            }
+            let failure = test_failure(shared, &e, ctx);
+            let mut state = shared.borrow_mut();
+            state.tests.record_assertion(failure);
+            state.tests.end_test();
+            return Ok(JsValue::undefined());
+        }
+    };
+
+    let Some(promise) = outcome
+        .as_object()
+        .and_then(|o| JsPromise::from_object(o.clone()).ok())
+    else {
+        shared.borrow_mut().tests.end_test();
+        return Ok(JsValue::undefined());
+    };
+    let Some(id) = shared.borrow_mut().tests.suspend_test() else {
+        return Ok(JsValue::undefined());
+    };
+    let on_fulfilled = settle_function(Rc::clone(shared), id, false, ctx);
+    let on_rejected = settle_function(Rc::clone(shared), id, true, ctx);
+    let settled = promise.then(Some(on_fulfilled), Some(on_rejected), ctx);
+    shared.borrow_mut().async_tests = Some(settled.clone());
+    Ok(settled.into())
+}
+
+/// The error to stop the handler with. An uncatchable one can't leave a
+/// module handler (boa panics rejecting the module with it), so there it is
+/// kept in `unwinding` and a plain error with the same message is thrown.
+fn unwind(shared: &Rc<RefCell<JsSharedState>>, error: JsError) -> JsError {
+    let mut state = shared.borrow_mut();
+    if !state.in_module || !(is_exit_signal(&error) || is_runtime_limit(&error)) {
+        return error;
+    }
+    let message = error.as_native().map(|e| e.message().to_string()).unwrap_or_default();
+    state.unwinding = Some(error);
+    JsNativeError::error().with_message(message).into()
+}
+
+/// A promise reaction that runs a test queued behind an async one.
+fn deferred_test_function(
+    shared: Rc<RefCell<JsSharedState>>,
+    name: String,
+    callback: JsObject,
+    context: &mut Context,
+) -> JsFunction {
+    // SAFETY: The captured callback is kept alive by this function object; the
+    // rest is Rc<RefCell<...>> used only from the single-threaded boa context.
+    let run = unsafe {
+        NativeFunction::from_closure(move |_this, _args, ctx| {
+            if shared.borrow().flow.exit {
+                return Ok(JsValue::undefined());
+            }
+            run_test(&shared, name.clone(), &callback, ctx)
+        })
+    };
+    run.to_js_function(context.realm())
+}
+
+/// The failure recorded for an error thrown (or rejected) by a test body.
+/// Failed `expect` assertions are reported as-is with their diff; anything
+/// else is an exception in the test body.
+fn test_failure(shared: &Rc<RefCell<JsSharedState>>, error: &JsError, ctx: &mut Context) -> AssertionRecord {
+    let (mut message, line) = {
+        let state = shared.borrow();
+        (state.source_map.describe_error(error), state.source_map.error_line(error))
+    };
+    if is_assertion_error(error, ctx) {
+        if let Some(diff) = assertion_diff(error, ctx) {
+            message.push_str(&diff);
+        }
+    } else {
+        message = format!("Exception: {message}");
+    }
+    AssertionRecord {
+        passed: false,
+        message,
+        line,
+    }
+}
+
+/// A promise reaction that ends the suspended async test `id`.
+fn settle_function(
+    shared: Rc<RefCell<JsSharedState>>,
+    id: usize,
+    rejected: bool,
+    context: &mut Context,
+) -> JsFunction {
+    // SAFETY: Same as the client functions — single-threaded boa context with Rc<RefCell<...>>.
+    let settle = unsafe {
+        NativeFunction::from_closure(move |_this, args, ctx| {
+            let failure = if rejected {
+                let reason = args.first().cloned().unwrap_or(JsValue::undefined());
+                let error = JsError::from_opaque(reason);
+                if shared.borrow().flow.exit {
+                    return Err(error);
+                }
+                Some(test_failure(&shared, &error, ctx))
+            } else {
+                None
+            };
+            shared.borrow_mut().tests.settle_test(id, failure);
+            Ok(JsValue::undefined())
+        })
+    };
+    settle.to_js_function(context.realm())
+}
+
+/// Fold a schema or snapshot check into a single assertion made at `line`.
+fn assertion_record(result: &TestResult, line: Option<usize>) -> AssertionRecord {
+    let message = match result.failure_message() {
+        Some(message) => format!("{}: {message}", result.name),
+        None => result.name.clone(),
+    };
+    AssertionRecord {
+        passed: result.passed,
+        message,
+        line,
+    }
+}
+
 fn build_global_object(
     shared: Rc<RefCell<JsSharedState>>,
     context: &mut Context,
@@ -141,7 +667,7 @@ fn build_global_object(
     let set_fn = unsafe {
         NativeFunction::from_closure(move |_this, args, ctx| {
             let name = args
-                .get(0)
+                .first()
                 .cloned()
                 .unwrap_or(JsValue::undefined())
                 .to_string(ctx)?
@@ -163,10 +689,9 @@ fn build_global_object(
                 value.to_string(ctx)?.to_std_string_escaped()
             };
 
-            shared_set
-                .borrow_mut()
-                .global_vars
-                .insert(name, value_str);
+            let mut state = shared_set.borrow_mut();
+            state.removed_globals.remove(&name);
+            state.global_vars.insert(name, value_str);
 
             Ok(JsValue::undefined())
         })
@@ -177,7 +702,7 @@ fn build_global_object(
     let get_fn = unsafe {
         NativeFunction::from_closure(move |_this, args, ctx| {
             let name = args
-                .get(0)
+                .first()
                 .cloned()
                 .unwrap_or(JsValue::undefined())
                 .to_string(ctx)?
@@ -203,9 +728,51 @@ fn build_global_object(
         })
     };
 
+    let shared_clear = Rc::clone(&shared);
+    // SAFETY: Single-threaded boa context with Rc<RefCell<...>>.
+    let clear_fn = unsafe {
+        NativeFunction::from_closure(move |_this, args, ctx| {
+            let name = args
+                .first()
+                .cloned()
+                .unwrap_or(JsValue::undefined())
+                .to_string(ctx)?
+                .to_std_string_escaped();
+
+            let mut state = shared_clear.borrow_mut();
+            state.global_vars.remove(&name);
+            state.removed_globals.insert(name);
+
+            Ok(JsValue::undefined())
+        })
+    };
+
+    let shared_clear_all = Rc::clone(&shared);
+    // SAFETY: Single-threaded boa context with Rc<RefCell<...>>.
+    let clear_all_fn = unsafe {
+        NativeFunction::from_closure(move |_this, _args, _ctx| {
+            let mut state = shared_clear_all.borrow_mut();
+            let names: Vec<String> = state.global_vars.drain().map(|(k, _)| k).collect();
+            state.removed_globals.extend(names);
+
+            Ok(JsValue::undefined())
+        })
+    };
+
+    let shared_is_empty = Rc::clone(&shared);
+    // SAFETY: Single-threaded boa context with Rc<RefCell<...>>.
+    let is_empty_fn = unsafe {
+        NativeFunction::from_closure(move |_this, _args, _ctx| {
+            Ok(JsValue::from(shared_is_empty.borrow().global_vars.is_empty()))
+        })
+    };
+
     let global = ObjectInitializer::new(context)
         .function(set_fn, js_string!("set"), 2)
         .function(get_fn, js_string!("get"), 1)
+        .function(clear_fn, js_string!("clear"), 1)
+        .function(clear_all_fn, js_string!("clearAll"), 0)
+        .function(is_empty_fn, js_string!("isEmpty"), 0)
         .build();
 
     Ok(global.into())
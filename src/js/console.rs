@@ -0,0 +1,178 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use boa_engine::{
+    Context, JsResult, JsValue, NativeFunction, builtins::error::Error, js_string,
+    object::ObjectInitializer, property::Attribute,
+};
+use serde_json::Value;
+
+use crate::js::client::JsSharedState;
+use crate::js::runtime::{LogLevel, LogLine};
+
+/// Register the `console` global, whose methods append to the handler's log.
+pub fn register_console_global(
+    shared: Rc<RefCell<JsSharedState>>,
+    context: &mut Context,
+) -> JsResult<()> {
+    let mut console = ObjectInitializer::new(context);
+    for (name, level) in [
+        ("log", LogLevel::Log),
+        ("info", LogLevel::Info),
+        ("warn", LogLevel::Warn),
+        ("error", LogLevel::Error),
+        ("debug", LogLevel::Debug),
+    ] {
+        let shared = Rc::clone(&shared);
+        // SAFETY: The closure captures only Rc<RefCell<...>>, used from the
+        // single-threaded boa context.
+        let log_fn = unsafe {
+            NativeFunction::from_closure(move |_this, args, ctx| {
+                let message = format_args(args, ctx)?;
+                shared.borrow_mut().log_output.push(LogLine { level, message });
+                Ok(JsValue::undefined())
+            })
+        };
+        console.function(log_fn, js_string!(name), 0);
+    }
+
+    // console.table(data) — render an array or object of rows as a table
+    let shared_table = Rc::clone(&shared);
+    // SAFETY: Same as above.
+    let table_fn = unsafe {
+        NativeFunction::from_closure(move |_this, args, ctx| {
+            let data = args.first().cloned().unwrap_or(JsValue::undefined());
+            let message = match data.as_object().map(|_| data.to_json(ctx)) {
+                Some(Ok(Some(json @ (Value::Array(_) | Value::Object(_))))) => format_table(&json),
+                _ => format_args(args, ctx)?,
+            };
+            shared_table.borrow_mut().log_output.push(LogLine {
+                level: LogLevel::Log,
+                message,
+            });
+            Ok(JsValue::undefined())
+        })
+    };
+    console.function(table_fn, js_string!("table"), 1);
+
+    let console = console.build();
+    context.register_global_property(
+        js_string!("console"),
+        console,
+        Attribute::WRITABLE | Attribute::CONFIGURABLE,
+    )?;
+    Ok(())
+}
+
+/// Join log arguments with spaces: strings as-is, errors as `Name: message`,
+/// functions as `[Function name]` and other objects as pretty JSON.
+pub fn format_args(args: &[JsValue], context: &mut Context) -> JsResult<String> {
+    let mut parts = Vec::with_capacity(args.len());
+    for arg in args {
+        parts.push(format_value(arg, context)?);
+    }
+    Ok(parts.join(" "))
+}
+
+fn format_value(value: &JsValue, context: &mut Context) -> JsResult<String> {
+    let Some(obj) = value.as_object() else {
+        return Ok(value.to_string(context)?.to_std_string_escaped());
+    };
+    if obj.is_callable() {
+        let name = obj.get(js_string!("name"), context)?.to_string(context)?;
+        let name = name.to_std_string_escaped();
+        return Ok(if name.is_empty() {
+            "[Function]".to_string()
+        } else {
+            format!("[Function {name}]")
+        });
+    }
+    if obj.is::<Error>() {
+        return Ok(value.to_string(context)?.to_std_string_escaped());
+    }
+    // Cyclic values and BigInts cannot be serialized; show what boa shows
+    Ok(match value.to_json(context) {
+        Ok(Some(json)) => serde_json::to_string_pretty(&json).unwrap_or_default(),
+        _ => value.display().to_string(),
+    })
+}
+
+/// Lay out rows the way `console.table` does: one row per array element (or
+/// object entry) and one column per key seen in any row. Primitive rows go in
+/// a `Values` column.
+fn format_table(data: &Value) -> String {
+    let rows: Vec<(String, &Value)> = match data {
+        Value::Array(items) => items.iter().enumerate().map(|(i, v)| (i.to_string(), v)).collect(),
+        Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        _ => Vec::new(),
+    };
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut has_values = false;
+    for (_, row) in &rows {
+        match row {
+            Value::Object(map) => {
+                for key in map.keys() {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+            }
+            _ => has_values = true,
+        }
+    }
+
+    let mut header = vec!["(index)".to_string()];
+    header.extend(columns.iter().cloned());
+    if has_values {
+        header.push("Values".to_string());
+    }
+    let body: Vec<Vec<String>> = rows
+        .iter()
+        .map(|(index, row)| {
+            let mut cells = vec![index.clone()];
+            for column in &columns {
+                cells.push(row.get(column).map(cell_text).unwrap_or_default());
+            }
+            if has_values {
+                cells.push(if row.is_object() { String::new() } else { cell_text(row) });
+            }
+            cells
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            std::iter::once(&header)
+                .chain(&body)
+                .map(|cells| cells[i].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let border = |left: &str, mid: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+        format!("{left}{}{right}", segments.join(mid))
+    };
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, w)| format!(" {cell}{} ", " ".repeat(w - cell.chars().count())))
+            .collect();
+        format!("│{}│", padded.join("│"))
+    };
+
+    let mut out = vec![border("┌", "┬", "┐"), line(&header), border("├", "┼", "┤")];
+    out.extend(body.iter().map(|cells| line(cells)));
+    out.push(border("└", "┴", "┘"));
+    out.join("\n")
+}
+
+/// Strings without quotes; everything else as compact JSON.
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
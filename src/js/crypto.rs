@@ -0,0 +1,425 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use base64::Engine;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use boa_engine::{
+    Context, JsNativeError, JsResult, JsValue, NativeFunction, js_string,
+    object::ObjectInitializer, property::Attribute,
+};
+use hmac::digest::{Digest, KeyInit, core_api::BlockSizeUser};
+use hmac::{Mac, SimpleHmac};
+
+use crate::js::response::json_to_js;
+
+// Decoders accept input with or without `=` padding.
+const BASE64_STANDARD: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+const BASE64_URL_SAFE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[derive(Debug, Clone, Copy)]
+enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+const ALGORITHMS: [(&str, Algorithm); 5] = [
+    ("md5", Algorithm::Md5),
+    ("sha1", Algorithm::Sha1),
+    ("sha256", Algorithm::Sha256),
+    ("sha384", Algorithm::Sha384),
+    ("sha512", Algorithm::Sha512),
+];
+
+impl Algorithm {
+    fn digest(self, key: Option<&[u8]>, data: &[u8]) -> Vec<u8> {
+        match (self, key) {
+            (Self::Md5, None) => md5::Md5::digest(data).to_vec(),
+            (Self::Sha1, None) => sha1::Sha1::digest(data).to_vec(),
+            (Self::Sha256, None) => sha2::Sha256::digest(data).to_vec(),
+            (Self::Sha384, None) => sha2::Sha384::digest(data).to_vec(),
+            (Self::Sha512, None) => sha2::Sha512::digest(data).to_vec(),
+            (Self::Md5, Some(key)) => hmac_digest::<md5::Md5>(key, data),
+            (Self::Sha1, Some(key)) => hmac_digest::<sha1::Sha1>(key, data),
+            (Self::Sha256, Some(key)) => hmac_digest::<sha2::Sha256>(key, data),
+            (Self::Sha384, Some(key)) => hmac_digest::<sha2::Sha384>(key, data),
+            (Self::Sha512, Some(key)) => hmac_digest::<sha2::Sha512>(key, data),
+        }
+    }
+}
+
+fn hmac_digest<D: Digest + BlockSizeUser>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = <SimpleHmac<D> as KeyInit>::new_from_slice(key)
+        .expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Bytes fed to a hasher so far; hashed when `digest()` is called.
+#[derive(Debug)]
+struct HasherState {
+    algorithm: Algorithm,
+    key: Option<Vec<u8>>,
+    data: Vec<u8>,
+}
+
+/// Register the `crypto` and `jwt` globals (IntelliJ-compatible hashing,
+/// HMAC and encoding helpers) in the current realm. They stay writable so
+/// handlers may declare their own variables with these names.
+pub fn register_crypto_globals(context: &mut Context) -> JsResult<()> {
+    let crypto = build_crypto_object(context)?;
+    context.register_global_property(
+        js_string!("crypto"),
+        crypto,
+        Attribute::WRITABLE | Attribute::CONFIGURABLE,
+    )?;
+
+    let jwt = ObjectInitializer::new(context)
+        .function(NativeFunction::from_fn_ptr(jwt_decode), js_string!("decode"), 1)
+        .build();
+    context.register_global_property(
+        js_string!("jwt"),
+        jwt,
+        Attribute::WRITABLE | Attribute::CONFIGURABLE,
+    )?;
+
+    Ok(())
+}
+
+fn build_crypto_object(context: &mut Context) -> JsResult<JsValue> {
+    let mut hmac = ObjectInitializer::new(context);
+    for (name, algorithm) in ALGORITHMS {
+        hmac.function(
+            NativeFunction::from_copy_closure(move |_this, _args, ctx| {
+                build_hmac_builder(algorithm, ctx)
+            }),
+            js_string!(name),
+            0,
+        );
+    }
+    let hmac = hmac.build();
+
+    let base64 = build_codec_object(context, base64_encode, base64_decode);
+    let base64url = build_codec_object(context, base64url_encode, base64_decode);
+    let hex = build_codec_object(context, hex_encode, hex_decode);
+
+    let mut crypto = ObjectInitializer::new(context);
+    for (name, algorithm) in ALGORITHMS {
+        crypto.function(
+            NativeFunction::from_copy_closure(move |_this, _args, ctx| {
+                build_hasher(algorithm, None, ctx)
+            }),
+            js_string!(name),
+            0,
+        );
+    }
+    let crypto = crypto
+        .property(js_string!("hmac"), hmac, Attribute::READONLY)
+        .property(js_string!("base64"), base64, Attribute::READONLY)
+        .property(js_string!("base64url"), base64url, Attribute::READONLY)
+        .property(js_string!("hex"), hex, Attribute::READONLY)
+        .build();
+
+    Ok(crypto.into())
+}
+
+type NativeFn = fn(&JsValue, &[JsValue], &mut Context) -> JsResult<JsValue>;
+
+fn build_codec_object(context: &mut Context, encode: NativeFn, decode: NativeFn) -> JsValue {
+    ObjectInitializer::new(context)
+        .function(NativeFunction::from_fn_ptr(encode), js_string!("encode"), 1)
+        .function(NativeFunction::from_fn_ptr(decode), js_string!("decode"), 1)
+        .build()
+        .into()
+}
+
+/// `crypto.hmac.<alg>()` — pick the secret before hashing.
+fn build_hmac_builder(algorithm: Algorithm, context: &mut Context) -> JsResult<JsValue> {
+    let builder = ObjectInitializer::new(context)
+        .function(
+            NativeFunction::from_copy_closure(move |_this, args, ctx| {
+                let key = string_arg(args, 0, ctx)?.into_bytes();
+                build_hasher(algorithm, Some(key), ctx)
+            }),
+            js_string!("withTextSecret"),
+            1,
+        )
+        .function(
+            NativeFunction::from_copy_closure(move |_this, args, ctx| {
+                let key = decode_hex(&string_arg(args, 0, ctx)?)?;
+                build_hasher(algorithm, Some(key), ctx)
+            }),
+            js_string!("withHexSecret"),
+            1,
+        )
+        .function(
+            NativeFunction::from_copy_closure(move |_this, args, ctx| {
+                let key = decode_base64(&string_arg(args, 0, ctx)?)?;
+                build_hasher(algorithm, Some(key), ctx)
+            }),
+            js_string!("withBase64Secret"),
+            2,
+        )
+        .build();
+
+    Ok(builder.into())
+}
+
+/// A hasher with `updateWithText/Hex/Base64(...)` (chainable) and `digest()`.
+fn build_hasher(
+    algorithm: Algorithm,
+    key: Option<Vec<u8>>,
+    context: &mut Context,
+) -> JsResult<JsValue> {
+    let state = Rc::new(RefCell::new(HasherState {
+        algorithm,
+        key,
+        data: Vec::new(),
+    }));
+
+    let shared_text = Rc::clone(&state);
+    // SAFETY: The closure captures only Rc<RefCell<...>>, used within a single-threaded boa context.
+    let update_text_fn = unsafe {
+        NativeFunction::from_closure(move |this, args, ctx| {
+            let text = string_arg(args, 0, ctx)?;
+            shared_text.borrow_mut().data.extend_from_slice(text.as_bytes());
+            Ok(this.clone())
+        })
+    };
+
+    let shared_hex = Rc::clone(&state);
+    // SAFETY: Same as above.
+    let update_hex_fn = unsafe {
+        NativeFunction::from_closure(move |this, args, ctx| {
+            let bytes = decode_hex(&string_arg(args, 0, ctx)?)?;
+            shared_hex.borrow_mut().data.extend_from_slice(&bytes);
+            Ok(this.clone())
+        })
+    };
+
+    let shared_base64 = Rc::clone(&state);
+    // SAFETY: Same as above.
+    let update_base64_fn = unsafe {
+        NativeFunction::from_closure(move |this, args, ctx| {
+            let bytes = decode_base64(&string_arg(args, 0, ctx)?)?;
+            shared_base64.borrow_mut().data.extend_from_slice(&bytes);
+            Ok(this.clone())
+        })
+    };
+
+    let shared_digest = Rc::clone(&state);
+    // SAFETY: Same as above.
+    let digest_fn = unsafe {
+        NativeFunction::from_closure(move |_this, _args, ctx| {
+            let state = shared_digest.borrow();
+            let bytes = state.algorithm.digest(state.key.as_deref(), &state.data);
+            Ok(build_digest(bytes, ctx))
+        })
+    };
+
+    let hasher = ObjectInitializer::new(context)
+        .function(update_text_fn, js_string!("updateWithText"), 2)
+        .function(update_hex_fn, js_string!("updateWithHex"), 1)
+        .function(update_base64_fn, js_string!("updateWithBase64"), 2)
+        .function(digest_fn, js_string!("digest"), 0)
+        .build();
+
+    Ok(hasher.into())
+}
+
+/// A computed digest with `toHex()` and `toBase64(urlSafe)`.
+fn build_digest(bytes: Vec<u8>, context: &mut Context) -> JsValue {
+    ObjectInitializer::new(context)
+        .function(
+            NativeFunction::from_copy_closure_with_captures(
+                |_this, _args, bytes, _ctx| Ok(js_string!(hex::encode(bytes)).into()),
+                bytes.clone(),
+            ),
+            js_string!("toHex"),
+            0,
+        )
+        .function(
+            NativeFunction::from_copy_closure_with_captures(
+                |_this, args, bytes, _ctx| {
+                    let url_safe = args.first().is_some_and(JsValue::to_boolean);
+                    let encoded = if url_safe {
+                        BASE64_URL_SAFE.encode(bytes)
+                    } else {
+                        BASE64_STANDARD.encode(bytes)
+                    };
+                    Ok(js_string!(encoded).into())
+                },
+                bytes,
+            ),
+            js_string!("toBase64"),
+            1,
+        )
+        .build()
+        .into()
+}
+
+fn base64_encode(_this: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let text = string_arg(args, 0, ctx)?;
+    Ok(js_string!(BASE64_STANDARD.encode(text)).into())
+}
+
+fn base64url_encode(_this: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let text = string_arg(args, 0, ctx)?;
+    Ok(js_string!(BASE64_URL_SAFE.encode(text)).into())
+}
+
+fn base64_decode(_this: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let bytes = decode_base64(&string_arg(args, 0, ctx)?)?;
+    Ok(js_string!(String::from_utf8_lossy(&bytes).into_owned()).into())
+}
+
+fn hex_encode(_this: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let text = string_arg(args, 0, ctx)?;
+    Ok(js_string!(hex::encode(text)).into())
+}
+
+fn hex_decode(_this: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let bytes = decode_hex(&string_arg(args, 0, ctx)?)?;
+    Ok(js_string!(String::from_utf8_lossy(&bytes).into_owned()).into())
+}
+
+/// `jwt.decode(token)` — `{ header, payload, signature }` without verifying the signature.
+fn jwt_decode(_this: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let token = string_arg(args, 0, ctx)?;
+    let token = token.trim().trim_start_matches("Bearer ").trim();
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err(JsNativeError::typ()
+            .with_message(format!(
+                "jwt.decode: expected 3 dot-separated parts, got {}",
+                parts.len()
+            ))
+            .into());
+    }
+
+    let header = decode_jwt_part(parts[0], "header")?;
+    let payload = decode_jwt_part(parts[1], "payload")?;
+    let header = json_to_js(&header, ctx)?;
+    let payload = json_to_js(&payload, ctx)?;
+
+    let decoded = ObjectInitializer::new(ctx)
+        .property(js_string!("header"), header, Attribute::all())
+        .property(js_string!("payload"), payload, Attribute::all())
+        .property(js_string!("signature"), js_string!(parts[2]), Attribute::all())
+        .build();
+    Ok(decoded.into())
+}
+
+fn decode_jwt_part(part: &str, what: &str) -> JsResult<serde_json::Value> {
+    let bytes = decode_base64(part)?;
+    serde_json::from_slice(&bytes).map_err(|e| {
+        JsNativeError::typ()
+            .with_message(format!("jwt.decode: {what} is not valid JSON: {e}"))
+            .into()
+    })
+}
+
+/// Decode standard or URL-safe base64, with or without padding.
+fn decode_base64(input: &str) -> JsResult<Vec<u8>> {
+    let input = input.trim();
+    let result = if input.contains(['-', '_']) {
+        BASE64_URL_SAFE.decode(input)
+    } else {
+        BASE64_STANDARD.decode(input)
+    };
+    result.map_err(|e| {
+        JsNativeError::typ()
+            .with_message(format!("invalid base64: {e}"))
+            .into()
+    })
+}
+
+fn decode_hex(input: &str) -> JsResult<Vec<u8>> {
+    hex::decode(input.trim()).map_err(|e| {
+        JsNativeError::typ()
+            .with_message(format!("invalid hex: {e}"))
+            .into()
+    })
+}
+
+fn string_arg(args: &[JsValue], index: usize, ctx: &mut Context) -> JsResult<String> {
+    Ok(args
+        .get(index)
+        .cloned()
+        .unwrap_or(JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_escaped())
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::{Context, Source};
+
+    use super::register_crypto_globals;
+
+    fn eval(script: &str) -> String {
+        let mut context = Context::default();
+        register_crypto_globals(&mut context).unwrap();
+        context
+            .eval(Source::from_bytes(script))
+            .unwrap()
+            .to_string(&mut context)
+            .unwrap()
+            .to_std_string_escaped()
+    }
+
+    #[test]
+    fn hashes_match_known_vectors() {
+        assert_eq!(
+            eval(r#"crypto.sha256().updateWithText("abc").digest().toHex()"#),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            eval(r#"crypto.md5().digest().toHex()"#),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+        assert_eq!(
+            eval(r#"crypto.sha1().updateWithText("a").updateWithHex("6263").digest().toHex()"#),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+    }
+
+    #[test]
+    fn hmac_and_encodings() {
+        assert_eq!(
+            eval(
+                r#"crypto.hmac.sha256().withTextSecret("key")
+                    .updateWithText("The quick brown fox jumps over the lazy dog")
+                    .digest().toHex()"#
+            ),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+        assert_eq!(eval(r#"crypto.base64.encode("hi?>")"#), "aGk/Pg==");
+        assert_eq!(eval(r#"crypto.base64url.encode("hi?>")"#), "aGk_Pg");
+        assert_eq!(eval(r#"crypto.base64.decode("aGk_Pg")"#), "hi?>");
+        assert_eq!(eval(r#"crypto.hex.decode(crypto.hex.encode("hi"))"#), "hi");
+    }
+
+    #[test]
+    fn jwt_decode_returns_header_and_payload() {
+        // {"alg":"HS256","typ":"JWT"}.{"sub":"42","admin":true}
+        let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiI0MiIsImFkbWluIjp0cnVlfQ.sig";
+        assert_eq!(
+            eval(&format!(
+                r#"var t = jwt.decode("{token}"); t.header.alg + " " + t.payload.sub + " " + t.payload.admin"#
+            )),
+            "HS256 42 true"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use boa_engine::{
+    Context, JsNativeError, JsObject, JsResult, JsString, JsValue, NativeFunction,
+    builtins::promise::OperationType,
+    context::HostHooks,
+    job::{Job, JobExecutor, PromiseJob, SimpleJobExecutor},
+    object::builtins::JsFunction,
+    property::Attribute,
+};
+
+/// A `setTimeout`/`setInterval` callback waiting for its turn.
+struct Timer {
+    id: u32,
+    due: Instant,
+    callback: JsFunction,
+    args: Vec<JsValue>,
+    /// Set for `setInterval`; the timer is rescheduled after each run.
+    interval: Option<Duration>,
+    /// The test context active when the timer was set.
+    test_context: Option<usize>,
+}
+
+/// How a run of the event loop ended.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoopExit {
+    /// No promise jobs or one-shot timers are left, and nothing is waiting on
+    /// an interval.
+    Idle,
+    /// A timer is still due after the handler's deadline.
+    Deadline,
+}
+
+/// The handler thread's event loop: promise jobs and timers are kept here
+/// (other jobs go through boa's [`SimpleJobExecutor`]), and promises rejected
+/// without a handler are remembered so they can be reported once the handler
+/// is done.
+///
+/// Promise jobs and timers carry the test context (see
+/// [`crate::js::client::TestTracker`]) that was active when they were
+/// scheduled, and run with it active again.
+///
+/// One loop serves every handler on the thread; [`EventLoop::clear`] drops
+/// whatever a handler left behind before the next one starts.
+#[derive(Default)]
+pub struct EventLoop {
+    jobs: Rc<SimpleJobExecutor>,
+    promise_jobs: RefCell<VecDeque<(Option<usize>, PromiseJob)>>,
+    test_context: Rc<Cell<Option<usize>>>,
+    timers: RefCell<Vec<Timer>>,
+    next_timer_id: Cell<u32>,
+    /// The timer whose callback is executing, until it cancels itself.
+    running_timer: Cell<Option<u32>>,
+    unhandled_rejections: RefCell<Vec<JsObject>>,
+}
+
+impl EventLoop {
+    /// Run promise jobs and timers until only intervals (or nothing) remain.
+    /// Intervals keep the loop going only while `waiting` says something (an
+    /// async test, say) may still be settled by one. Timers sleep the thread
+    /// until they are due; one that would fire after `deadline` ends the loop
+    /// instead. `stop` is checked between timers so `client.exit()` inside a
+    /// callback ends the handler.
+    pub fn run(
+        &self,
+        context: &mut Context,
+        deadline: Instant,
+        stop: impl Fn() -> bool,
+        waiting: impl Fn() -> bool,
+    ) -> JsResult<LoopExit> {
+        loop {
+            self.run_promise_jobs(context)?;
+            if stop() {
+                return Ok(LoopExit::Idle);
+            }
+
+            let next = {
+                let timers = self.timers.borrow();
+                if timers.iter().all(|t| t.interval.is_some()) && !waiting() {
+                    return Ok(LoopExit::Idle);
+                }
+                timers.iter().map(|t| (t.due, t.id)).min()
+            };
+            let Some((due, id)) = next else {
+                return Ok(LoopExit::Idle);
+            };
+            if due > deadline {
+                return Ok(LoopExit::Deadline);
+            }
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+
+            let Some(timer) = self.take_timer(id) else {
+                continue;
+            };
+            self.running_timer.set(Some(id));
+            let outer = self.test_context.replace(timer.test_context);
+            let result = timer.callback.call(&JsValue::undefined(), &timer.args, context);
+            self.test_context.set(outer);
+            // clearInterval() inside the callback unsets the running timer
+            if let Some(interval) = timer.interval
+                && self.running_timer.take() == Some(id)
+            {
+                self.timers.borrow_mut().push(Timer {
+                    due: Instant::now() + interval,
+                    ..timer
+                });
+            }
+            result?;
+        }
+    }
+
+    /// The active test context, shared with the handler's test tracker.
+    pub fn test_context(&self) -> Rc<Cell<Option<usize>>> {
+        Rc::clone(&self.test_context)
+    }
+
+    /// Forget pending jobs, timers and rejections left by the previous handler.
+    pub fn clear(&self) {
+        self.promise_jobs.borrow_mut().clear();
+        self.timers.borrow_mut().clear();
+        self.test_context.set(None);
+        self.unhandled_rejections.borrow_mut().clear();
+    }
+
+    /// Promises that were rejected and never got a handler, oldest first.
+    pub fn take_unhandled_rejections(&self) -> Vec<JsObject> {
+        std::mem::take(&mut *self.unhandled_rejections.borrow_mut())
+    }
+
+    /// Run promise jobs until none are left, each with the test context it was
+    /// enqueued under. Like boa's executor, an error drops the remaining jobs.
+    fn run_promise_jobs(&self, context: &mut Context) -> JsResult<()> {
+        loop {
+            let next = self.promise_jobs.borrow_mut().pop_front();
+            let Some((test_context, job)) = next else {
+                Rc::clone(&self.jobs).run_jobs(context)?;
+                if self.promise_jobs.borrow().is_empty() {
+                    return Ok(());
+                }
+                continue;
+            };
+            let outer = self.test_context.replace(test_context);
+            let result = job.call(context);
+            self.test_context.set(outer);
+            if let Err(e) = result {
+                self.promise_jobs.borrow_mut().clear();
+                return Err(e);
+            }
+        }
+    }
+
+    fn take_timer(&self, id: u32) -> Option<Timer> {
+        let mut timers = self.timers.borrow_mut();
+        let index = timers.iter().position(|t| t.id == id)?;
+        Some(timers.remove(index))
+    }
+
+    fn add_timer(&self, callback: JsFunction, delay: Duration, args: Vec<JsValue>, repeat: bool) -> u32 {
+        let id = self.next_timer_id.get() + 1;
+        self.next_timer_id.set(id);
+        self.timers.borrow_mut().push(Timer {
+            id,
+            due: Instant::now() + delay,
+            callback,
+            args,
+            interval: repeat.then_some(delay),
+            test_context: self.test_context.get(),
+        });
+        id
+    }
+
+    fn cancel_timer(&self, id: u32) {
+        if self.running_timer.get() == Some(id) {
+            self.running_timer.set(None);
+        }
+        self.take_timer(id);
+    }
+}
+
+impl JobExecutor for EventLoop {
+    fn enqueue_job(self: Rc<Self>, job: Job, context: &mut Context) {
+        match job {
+            Job::PromiseJob(job) => {
+                self.promise_jobs.borrow_mut().push_back((self.test_context.get(), job));
+            }
+            job => Rc::clone(&self.jobs).enqueue_job(job, context),
+        }
+    }
+
+    fn run_jobs(self: Rc<Self>, context: &mut Context) -> JsResult<()> {
+        self.run_promise_jobs(context)
+    }
+}
+
+impl HostHooks for EventLoop {
+    fn promise_rejection_tracker(
+        &self,
+        promise: &JsObject,
+        operation: OperationType,
+        _context: &mut Context,
+    ) {
+        let mut rejections = self.unhandled_rejections.borrow_mut();
+        match operation {
+            OperationType::Reject => rejections.push(promise.clone()),
+            OperationType::Handle => rejections.retain(|p| !JsObject::equals(p, promise)),
+        }
+    }
+}
+
+/// Register `setTimeout`, `setInterval`, `clearTimeout` and `clearInterval`
+/// in the current realm, backed by `event_loop`.
+pub fn register_timer_globals(event_loop: &Rc<EventLoop>, context: &mut Context) -> JsResult<()> {
+    for (name, repeat) in [("setTimeout", false), ("setInterval", true)] {
+        let event_loop = Rc::clone(event_loop);
+        // SAFETY: The closure captures only an Rc to the thread's event loop,
+        // which is only used from this single-threaded boa context.
+        let set_fn = unsafe {
+            NativeFunction::from_closure(move |_this, args, ctx| {
+                let Some(callback) = args
+                    .first()
+                    .and_then(JsValue::as_callable)
+                    .and_then(|f| JsFunction::from_object(f.clone()))
+                else {
+                    return Err(JsNativeError::typ()
+                        .with_message(format!("{name}: callback is not a function"))
+                        .into());
+                };
+                let delay = args
+                    .get(1)
+                    .cloned()
+                    .unwrap_or(JsValue::undefined())
+                    .to_number(ctx)?;
+                let delay = if delay.is_finite() && delay > 0.0 { delay as u64 } else { 0 };
+                let rest = args.get(2..).unwrap_or_default().to_vec();
+
+                let id = event_loop.add_timer(callback, Duration::from_millis(delay), rest, repeat);
+                Ok(JsValue::from(id))
+            })
+        };
+        let set_fn = set_fn.to_js_function(context.realm());
+        context.register_global_property(
+            JsString::from(name),
+            set_fn,
+            Attribute::WRITABLE | Attribute::CONFIGURABLE,
+        )?;
+    }
+
+    for name in ["clearTimeout", "clearInterval"] {
+        let event_loop = Rc::clone(event_loop);
+        // SAFETY: Same as above.
+        let clear_fn = unsafe {
+            NativeFunction::from_closure(move |_this, args, ctx| {
+                if let Some(id) = args.first().filter(|v| !v.is_null_or_undefined()) {
+                    event_loop.cancel_timer(id.to_u32(ctx)?);
+                }
+                Ok(JsValue::undefined())
+            })
+        };
+        let clear_fn = clear_fn.to_js_function(context.realm());
+        context.register_global_property(
+            JsString::from(name),
+            clear_fn,
+            Attribute::WRITABLE | Attribute::CONFIGURABLE,
+        )?;
+    }
+
+    Ok(())
+}
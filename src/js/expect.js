@@ -0,0 +1,280 @@
+// Chai-style `expect` for response handlers. Evaluated once per handler realm.
+// Failed assertions throw an AssertionError carrying `actual`, `expected` and
+// `showDiff`; client.test turns those into a failure message with a diff.
+(function (global) {
+    "use strict";
+
+    class AssertionError extends Error {
+        constructor(message, actual, expected, showDiff) {
+            super(message);
+            this.actual = actual;
+            this.expected = expected;
+            this.showDiff = showDiff === true;
+        }
+    }
+    AssertionError.prototype.name = "AssertionError";
+
+    function inspect(value) {
+        if (typeof value === "string") return JSON.stringify(value);
+        if (value === undefined) return "undefined";
+        if (typeof value === "function") return "[Function" + (value.name ? " " + value.name : "") + "]";
+        if (value instanceof RegExp) return String(value);
+        try {
+            var json = JSON.stringify(value);
+            return json === undefined ? String(value) : json;
+        } catch (e) {
+            return String(value);
+        }
+    }
+
+    function typeOf(value) {
+        if (value === null) return "null";
+        if (Array.isArray(value)) return "array";
+        if (value instanceof RegExp) return "regexp";
+        return typeof value;
+    }
+
+    function deepEqual(a, b) {
+        if (a === b || (a !== a && b !== b)) return true;
+        if (typeOf(a) !== typeOf(b) || typeof a !== "object" || a === null) return false;
+        if (Array.isArray(a)) {
+            if (a.length !== b.length) return false;
+            for (var i = 0; i < a.length; i++) {
+                if (!deepEqual(a[i], b[i])) return false;
+            }
+            return true;
+        }
+        var keysA = Object.keys(a), keysB = Object.keys(b);
+        if (keysA.length !== keysB.length) return false;
+        for (var k = 0; k < keysA.length; k++) {
+            var key = keysA[k];
+            if (!Object.prototype.hasOwnProperty.call(b, key) || !deepEqual(a[key], b[key])) return false;
+        }
+        return true;
+    }
+
+    function sizeOf(value) {
+        if (value === null || value === undefined) return undefined;
+        if (typeof value === "string" || Array.isArray(value)) return value.length;
+        if (typeof value === "object") return Object.keys(value).length;
+        return undefined;
+    }
+
+    function Assertion(value, message) {
+        this._value = value;
+        this._message = message;
+        this._negate = false;
+        this._deep = false;
+    }
+
+    // Language chains that only improve readability.
+    ["to", "be", "been", "is", "that", "which", "and", "has", "have", "with", "at", "of", "same", "does"].forEach(function (word) {
+        Object.defineProperty(Assertion.prototype, word, { get: function () { return this; } });
+    });
+
+    Object.defineProperty(Assertion.prototype, "not", {
+        get: function () { this._negate = !this._negate; return this; }
+    });
+    Object.defineProperty(Assertion.prototype, "deep", {
+        get: function () { this._deep = true; return this; }
+    });
+
+    // Throw unless `passed` (inverted by `.not`). `diff` holds the actual and
+    // expected values to show when a positive assertion fails.
+    Assertion.prototype._assert = function (passed, positive, negative, diff) {
+        if (passed !== this._negate) return this;
+        var text = this._negate ? negative : positive;
+        if (this._message) text = this._message + ": " + text;
+        var showDiff = diff !== undefined && !this._negate;
+        throw new AssertionError(text,
+            showDiff ? diff.actual : this._value,
+            showDiff ? diff.expected : undefined,
+            showDiff);
+    };
+
+    // Terminal properties: expect(x).to.be.true
+    function terminal(name, check, description) {
+        Object.defineProperty(Assertion.prototype, name, {
+            get: function () {
+                var v = this._value;
+                return this._assert(check(v),
+                    "expected " + inspect(v) + " to be " + description,
+                    "expected " + inspect(v) + " not to be " + description);
+            }
+        });
+    }
+    terminal("ok", function (v) { return !!v; }, "truthy");
+    terminal("true", function (v) { return v === true; }, "true");
+    terminal("false", function (v) { return v === false; }, "false");
+    terminal("null", function (v) { return v === null; }, "null");
+    terminal("undefined", function (v) { return v === undefined; }, "undefined");
+    terminal("NaN", function (v) { return v !== v; }, "NaN");
+    terminal("exist", function (v) { return v !== null && v !== undefined; }, "defined");
+    terminal("empty", function (v) { return sizeOf(v) === 0; }, "empty");
+
+    Assertion.prototype.equal = function (expected) {
+        var v = this._value;
+        var passed = this._deep ? deepEqual(v, expected) : v === expected;
+        var verb = this._deep ? " to deeply equal " : " to equal ";
+        return this._assert(passed,
+            "expected " + inspect(v) + verb + inspect(expected),
+            "expected " + inspect(v) + " not" + verb.slice(3) + inspect(expected),
+            { actual: v, expected: expected });
+    };
+    Assertion.prototype.equals = Assertion.prototype.equal;
+    Assertion.prototype.eq = Assertion.prototype.equal;
+    Assertion.prototype.eql = function (expected) {
+        this._deep = true;
+        return this.equal(expected);
+    };
+
+    Assertion.prototype.a = function (type) {
+        var actual = typeOf(this._value);
+        var wanted = String(type).toLowerCase();
+        return this._assert(actual === wanted,
+            "expected " + inspect(this._value) + " to be a " + wanted + " but got " + actual,
+            "expected " + inspect(this._value) + " not to be a " + wanted);
+    };
+    Assertion.prototype.an = Assertion.prototype.a;
+
+    Assertion.prototype.include = function (item) {
+        var v = this._value, passed = false;
+        if (typeof v === "string") {
+            passed = v.indexOf(item) !== -1;
+        } else if (Array.isArray(v)) {
+            for (var i = 0; i < v.length && !passed; i++) {
+                passed = this._deep ? deepEqual(v[i], item) : v[i] === item;
+            }
+        } else if (v !== null && typeof v === "object" && item !== null && typeof item === "object") {
+            passed = Object.keys(item).every(function (key) { return deepEqual(v[key], item[key]); });
+        }
+        return this._assert(passed,
+            "expected " + inspect(v) + " to include " + inspect(item),
+            "expected " + inspect(v) + " not to include " + inspect(item));
+    };
+    Assertion.prototype.includes = Assertion.prototype.include;
+    Assertion.prototype.contain = Assertion.prototype.include;
+    Assertion.prototype.contains = Assertion.prototype.include;
+
+    Assertion.prototype.match = function (pattern) {
+        var v = this._value;
+        var re = pattern instanceof RegExp ? pattern : new RegExp(pattern);
+        return this._assert(re.test(String(v)),
+            "expected " + inspect(v) + " to match " + String(re),
+            "expected " + inspect(v) + " not to match " + String(re));
+    };
+    Assertion.prototype.matches = Assertion.prototype.match;
+
+    Assertion.prototype.property = function (name, expected) {
+        var v = this._value;
+        var has = v !== null && v !== undefined && name in Object(v);
+        var actual = has ? v[name] : undefined;
+        if (arguments.length < 2) {
+            this._assert(has,
+                "expected " + inspect(v) + " to have property " + inspect(name),
+                "expected " + inspect(v) + " not to have property " + inspect(name));
+            return new Assertion(actual, this._message);
+        }
+        var passed = has && (this._deep ? deepEqual(actual, expected) : actual === expected);
+        this._assert(passed,
+            has
+                ? "expected property " + inspect(name) + " to equal " + inspect(expected) + " but got " + inspect(actual)
+                : "expected " + inspect(v) + " to have property " + inspect(name),
+            "expected property " + inspect(name) + " not to equal " + inspect(expected),
+            { actual: actual, expected: expected });
+        return new Assertion(actual, this._message);
+    };
+
+    Assertion.prototype.lengthOf = function (n) {
+        var size = sizeOf(this._value);
+        return this._assert(size === n,
+            "expected " + inspect(this._value) + " to have length " + n + " but got " + size,
+            "expected " + inspect(this._value) + " not to have length " + n);
+    };
+    Assertion.prototype.length = Assertion.prototype.lengthOf;
+
+    function compare(name, op, describe) {
+        Assertion.prototype[name] = function (n) {
+            var v = this._value;
+            return this._assert(typeof v === "number" && op(v, n),
+                "expected " + inspect(v) + " to be " + describe + " " + inspect(n),
+                "expected " + inspect(v) + " not to be " + describe + " " + inspect(n));
+        };
+    }
+    compare("above", function (a, b) { return a > b; }, "above");
+    compare("below", function (a, b) { return a < b; }, "below");
+    compare("least", function (a, b) { return a >= b; }, "at least");
+    compare("most", function (a, b) { return a <= b; }, "at most");
+    Assertion.prototype.gt = Assertion.prototype.above;
+    Assertion.prototype.lt = Assertion.prototype.below;
+    Assertion.prototype.gte = Assertion.prototype.least;
+    Assertion.prototype.lte = Assertion.prototype.most;
+
+    Assertion.prototype.within = function (low, high) {
+        var v = this._value;
+        return this._assert(typeof v === "number" && v >= low && v <= high,
+            "expected " + inspect(v) + " to be within " + low + ".." + high,
+            "expected " + inspect(v) + " not to be within " + low + ".." + high);
+    };
+
+    Assertion.prototype.oneOf = function (list) {
+        var v = this._value;
+        var passed = list.some(function (item) { return deepEqual(item, v); });
+        return this._assert(passed,
+            "expected " + inspect(v) + " to be one of " + inspect(list),
+            "expected " + inspect(v) + " not to be one of " + inspect(list));
+    };
+
+    // HTTP-specific assertions on the `response` object.
+    Assertion.prototype.status = function (code) {
+        var actual = this._value === null || this._value === undefined ? undefined : this._value.status;
+        return this._assert(actual === code,
+            "expected response to have status " + code + " but got " + actual,
+            "expected response not to have status " + code);
+    };
+
+    Assertion.prototype.header = function (name, expected) {
+        var headers = this._value && this._value.headers;
+        var actual = headers ? headers.valueOf(name) : null;
+        if (arguments.length < 2) {
+            return this._assert(actual !== null,
+                "expected response to have header " + inspect(name),
+                "expected response not to have header " + inspect(name));
+        }
+        var passed = expected instanceof RegExp ? actual !== null && expected.test(actual) : actual === expected;
+        return this._assert(passed,
+            "expected header " + inspect(name) + " to be " + inspect(expected) + " but got " + inspect(actual),
+            "expected header " + inspect(name) + " not to be " + inspect(expected));
+    };
+
+    Assertion.prototype.satisfy = function (predicate) {
+        return this._assert(!!predicate(this._value),
+            "expected " + inspect(this._value) + " to satisfy " + inspect(predicate),
+            "expected " + inspect(this._value) + " not to satisfy " + inspect(predicate));
+    };
+
+    Assertion.prototype.throw = function (pattern) {
+        var threw = false, error;
+        try {
+            this._value();
+        } catch (e) {
+            threw = true;
+            error = e;
+        }
+        var message = error && error.message !== undefined ? String(error.message) : String(error);
+        var passed = threw && (pattern === undefined ||
+            (pattern instanceof RegExp ? pattern.test(message) : message.indexOf(pattern) !== -1));
+        var what = pattern === undefined ? "" : " matching " + inspect(pattern);
+        return this._assert(passed,
+            "expected function to throw" + what + (threw ? " but it threw " + inspect(message) : ""),
+            "expected function not to throw" + what + " but it threw " + inspect(message));
+    };
+
+    global.expect = function (value, message) {
+        return new Assertion(value, message === undefined ? undefined : String(message));
+    };
+    global.expect.fail = function (message) {
+        throw new AssertionError(message === undefined ? "expect.fail()" : String(message));
+    };
+    global.AssertionError = AssertionError;
+})(globalThis);
@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use boa_engine::{Context, JsError, JsObject, JsResult, JsValue, Source, js_string};
+
+use crate::diff::diff_lines;
+
+/// Path given to the `expect` library source so its frames can be hidden from
+/// handler backtraces.
+pub const EXPECT_SOURCE_PATH: &str = "<expect>";
+
+const EXPECT_SOURCE: &str = include_str!("expect.js");
+
+/// Define the chai-style `expect` global and `AssertionError` in the current realm.
+pub fn register_expect_global(context: &mut Context) -> JsResult<()> {
+    context.eval(Source::from_bytes(EXPECT_SOURCE).with_path(Path::new(EXPECT_SOURCE_PATH)))?;
+    Ok(())
+}
+
+/// The error object thrown by a failed `expect` assertion, if `error` is one.
+fn assertion_object(error: &JsError, context: &mut Context) -> Option<JsObject> {
+    let obj = error.as_opaque()?.as_object()?.clone();
+    let name = obj.get(js_string!("name"), context).ok()?;
+    (name.as_string()?.to_std_string_escaped() == "AssertionError").then_some(obj)
+}
+
+/// Whether `error` is a failed `expect` assertion rather than an exception.
+pub fn is_assertion_error(error: &JsError, context: &mut Context) -> bool {
+    assertion_object(error, context).is_some()
+}
+
+/// Render the expected/actual diff carried by a failed equality assertion.
+pub fn assertion_diff(error: &JsError, context: &mut Context) -> Option<String> {
+    let obj = assertion_object(error, context)?;
+    if !obj.get(js_string!("showDiff"), context).ok()?.to_boolean() {
+        return None;
+    }
+    let actual = obj.get(js_string!("actual"), context).ok()?;
+    let expected = obj.get(js_string!("expected"), context).ok()?;
+    let actual = pretty(&actual, context);
+    let expected = pretty(&expected, context);
+    if actual == expected {
+        return None;
+    }
+
+    let mut out = String::from("\n  - expected\n  + actual");
+    for line in diff_lines(&expected, &actual) {
+        out.push('\n');
+        out.push_str(&line);
+    }
+    Some(out)
+}
+
+/// Pretty-print a value as JSON, falling back to its string form.
+fn pretty(value: &JsValue, context: &mut Context) -> String {
+    if value.is_undefined() {
+        return "undefined".to_string();
+    }
+    match value.to_json(context) {
+        Ok(Some(json)) => serde_json::to_string_pretty(&json).unwrap_or_default(),
+        _ => value.display().to_string(),
+    }
+}
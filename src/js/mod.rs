@@ -1,5 +1,15 @@
 mod client;
+mod console;
+mod crypto;
+mod event_loop;
+mod expect;
+mod modules;
+mod query;
 mod response;
 mod runtime;
+mod source_map;
 
-pub use runtime::{execute_handler, TestResult};
+pub use runtime::{
+    FlowControl, HandlerLimits, HandlerRuntime, LogLevel, LogLine, ScriptOrigin, TestResult,
+};
+pub(crate) use query::is_definite_path;
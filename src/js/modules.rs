@@ -0,0 +1,336 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use boa_engine::{
+    Context, JsNativeError, JsObject, JsResult, JsString, JsValue, Module, NativeFunction, Source,
+    ast::{ModuleItem, scope::Scope},
+    interner::Interner,
+    js_string,
+    module::{ModuleLoader, Referrer},
+    object::{FunctionObjectBuilder, ObjectInitializer},
+    parser::Parser,
+    property::{Attribute, PropertyDescriptor},
+    realm::Realm,
+};
+
+use crate::js::source_map::HANDLER_SOURCE_PATH;
+
+/// Extensions tried, in order, when a module specifier has none.
+const MODULE_EXTENSIONS: [&str; 3] = ["js", "mjs", "cjs"];
+
+/// Handler globals that hold per-handler state. Modules see the running
+/// handler's values through getters of the same names.
+const HANDLER_GLOBALS: [&str; 3] = ["client", "response", "console"];
+
+/// Loads local ES modules (`import`) and CommonJS modules (`require`) for
+/// handlers. Relative specifiers in a handler resolve against the directory
+/// of its `.http` file; inside a module they resolve against that module.
+/// Handlers with `import` declarations are ES modules themselves, and their
+/// imports go through this loader like any other module's.
+///
+/// Each module is evaluated once per run, so module-level state is shared by
+/// every handler that imports it. Modules live in their own realm, which
+/// outlives the handlers' realms; its `client`, `response` and `console`
+/// always refer to the handler currently running.
+#[derive(Debug, Default)]
+pub struct ModuleRegistry {
+    base_dir: RefCell<PathBuf>,
+    es_modules: RefCell<HashMap<PathBuf, Module>>,
+    commonjs_exports: RefCell<HashMap<PathBuf, JsValue>>,
+    realm: RefCell<Option<Realm>>,
+    /// The running handler's values of [`HANDLER_GLOBALS`].
+    handler_globals: RefCell<Option<JsObject>>,
+    /// A function of the module realm's global scope that calls its argument,
+    /// so CommonJS wrappers are evaluated outside the caller's environments.
+    global_caller: RefCell<Option<JsObject>>,
+}
+
+impl ModuleRegistry {
+    /// Create the realm modules are evaluated in. `register` adds the globals
+    /// that don't depend on the handler (helpers, timers, `require`).
+    pub fn init_realm(
+        &self,
+        context: &mut Context,
+        register: impl FnOnce(&mut Context) -> JsResult<()>,
+    ) -> JsResult<()> {
+        let realm = context.create_realm()?;
+        let previous = context.enter_realm(realm.clone());
+        let result = define_handler_getters(context).and_then(|holder| {
+            register(context)?;
+            let caller = context.eval(Source::from_bytes("(function (run) { return run(); })"))?;
+            Ok((holder, caller.as_object()))
+        });
+        context.enter_realm(previous);
+        let (holder, caller) = result?;
+        *self.handler_globals.borrow_mut() = Some(holder);
+        *self.global_caller.borrow_mut() = caller;
+        *self.realm.borrow_mut() = Some(realm);
+        Ok(())
+    }
+
+    /// Point the module realm's handler globals at those of the current
+    /// (handler) realm.
+    pub fn bind_handler_globals(&self, context: &mut Context) -> JsResult<()> {
+        let Some(holder) = self.handler_globals.borrow().clone() else {
+            return Ok(());
+        };
+        for name in HANDLER_GLOBALS {
+            let value = context.global_object().get(js_string!(name), context)?;
+            holder.set(js_string!(name), value, false, context)?;
+        }
+        Ok(())
+    }
+
+    /// Run `f` in the module realm (the current realm if there is none).
+    fn in_realm<T>(
+        &self,
+        context: &mut Context,
+        f: impl FnOnce(&mut Context) -> JsResult<T>,
+    ) -> JsResult<T> {
+        let Some(realm) = self.realm.borrow().clone() else {
+            return f(context);
+        };
+        let previous = context.enter_realm(realm);
+        let result = f(context);
+        context.enter_realm(previous);
+        result
+    }
+
+    /// Set the directory that handler-relative specifiers resolve against.
+    pub fn set_base_dir(&self, dir: &Path) {
+        *self.base_dir.borrow_mut() = dir.to_path_buf();
+    }
+
+    /// Resolve `specifier` relative to `referrer` (a module file), or to the
+    /// base directory when the referrer is a handler.
+    fn resolve(&self, specifier: &str, referrer: Option<&Path>) -> JsResult<PathBuf> {
+        let spec = Path::new(specifier);
+        let is_relative = specifier.starts_with("./") || specifier.starts_with("../");
+        if !is_relative && !spec.is_absolute() {
+            return Err(JsNativeError::typ()
+                .with_message(format!(
+                    "cannot resolve module '{specifier}': only relative or absolute paths are supported"
+                ))
+                .into());
+        }
+
+        let dir = match referrer {
+            Some(path) if path != Path::new(HANDLER_SOURCE_PATH) => {
+                path.parent().map(Path::to_path_buf).unwrap_or_default()
+            }
+            _ => self.base_dir.borrow().clone(),
+        };
+        let joined = dir.join(spec);
+
+        let mut candidates = vec![joined.clone()];
+        if joined.extension().is_none() {
+            candidates.extend(MODULE_EXTENSIONS.iter().map(|ext| joined.with_extension(ext)));
+        }
+        // Canonical paths keep the cache stable however the module was reached.
+        if let Some(found) = candidates.iter().find(|c| c.is_file()) {
+            return Ok(found.canonicalize().unwrap_or_else(|_| found.clone()));
+        }
+        Err(JsNativeError::typ()
+            .with_message(format!(
+                "cannot find module '{specifier}' (looked for {})",
+                joined.display()
+            ))
+            .into())
+    }
+
+    /// `require(specifier)` for CommonJS modules.
+    fn require(
+        self: &Rc<Self>,
+        specifier: &str,
+        referrer: Option<&Path>,
+        context: &mut Context,
+    ) -> JsResult<JsValue> {
+        let path = self.resolve(specifier, referrer)?;
+        if let Some(exports) = self.commonjs_exports.borrow().get(&path) {
+            return Ok(exports.clone());
+        }
+
+        let code = std::fs::read_to_string(&path).map_err(|e| {
+            JsNativeError::typ().with_message(format!("could not read module '{specifier}': {e}"))
+        })?;
+        self.in_realm(context, |context| self.evaluate_commonjs(&path, &code, context))
+    }
+
+    fn evaluate_commonjs(
+        self: &Rc<Self>,
+        path: &Path,
+        code: &str,
+        context: &mut Context,
+    ) -> JsResult<JsValue> {
+        // Keep the wrapper on the module's first line so backtrace positions match the file.
+        let wrapped = format!("(function (module, exports, require) {{{code}\n}})");
+        let factory = self.eval_in_global_scope(wrapped, path, context)?;
+
+        let exports = JsObject::with_object_proto(context.intrinsics());
+        let module = ObjectInitializer::new(context)
+            .property(js_string!("exports"), exports.clone(), Attribute::all())
+            .build();
+
+        // Cache before running so circular requires see the partial exports.
+        self.commonjs_exports
+            .borrow_mut()
+            .insert(path.to_path_buf(), exports.clone().into());
+
+        let require = self.require_function(Some(path.to_path_buf()), context);
+        let Some(factory) = factory.as_callable() else {
+            return Err(JsNativeError::typ()
+                .with_message("module wrapper is not callable")
+                .into());
+        };
+        let args = [module.clone().into(), exports.into(), require];
+        if let Err(e) = factory.call(&JsValue::undefined(), &args, context) {
+            self.commonjs_exports.borrow_mut().remove(path);
+            return Err(e);
+        }
+
+        let exports = module.get(js_string!("exports"), context)?;
+        self.commonjs_exports
+            .borrow_mut()
+            .insert(path.to_path_buf(), exports.clone());
+        Ok(exports)
+    }
+
+    /// Evaluate `code` as a classic script of the current realm's global
+    /// scope. Evaluated straight from a native function, a script would run
+    /// in the caller's environments, which boa can't declare functions in
+    /// when the caller is an ES module (a handler with `import`s, say).
+    fn eval_in_global_scope(
+        &self,
+        code: String,
+        path: &Path,
+        context: &mut Context,
+    ) -> JsResult<JsValue> {
+        let Some(caller) = self.global_caller.borrow().clone() else {
+            return context.eval(Source::from_bytes(&code).with_path(path));
+        };
+        let path = path.to_path_buf();
+        // SAFETY: The closure captures only a String and a PathBuf, used within
+        // a single-threaded boa context.
+        let run = unsafe {
+            NativeFunction::from_closure(move |_this, _args, ctx| {
+                ctx.eval(Source::from_bytes(&code).with_path(&path))
+            })
+        };
+        let run = FunctionObjectBuilder::new(context.realm(), run).build();
+        caller.call(&JsValue::undefined(), &[run.into()], context)
+    }
+
+    /// Build a `require` function bound to `referrer` (None for handlers).
+    pub fn require_function(
+        self: &Rc<Self>,
+        referrer: Option<PathBuf>,
+        context: &mut Context,
+    ) -> JsValue {
+        let registry = Rc::clone(self);
+        // SAFETY: The closure captures only an Rc and a PathBuf, used within a
+        // single-threaded boa context.
+        let require_fn = unsafe {
+            NativeFunction::from_closure(move |_this, args, ctx| {
+                let specifier = args
+                    .first()
+                    .cloned()
+                    .unwrap_or(JsValue::undefined())
+                    .to_string(ctx)?
+                    .to_std_string_escaped();
+                registry.require(&specifier, referrer.as_deref(), ctx)
+            })
+        };
+        FunctionObjectBuilder::new(context.realm(), require_fn)
+            .name(js_string!("require"))
+            .length(1)
+            .build()
+            .into()
+    }
+}
+
+impl ModuleLoader for ModuleRegistry {
+    async fn load_imported_module(
+        self: Rc<Self>,
+        referrer: Referrer,
+        specifier: JsString,
+        context: &RefCell<&mut Context>,
+    ) -> JsResult<Module> {
+        let specifier = specifier.to_std_string_escaped();
+        let path = self.resolve(&specifier, referrer.path())?;
+        if let Some(module) = self.es_modules.borrow().get(&path) {
+            return Ok(module.clone());
+        }
+
+        let realm = self.realm.borrow().clone();
+        let module = parse_module(&path, &specifier, realm, &mut context.borrow_mut())?;
+        self.es_modules.borrow_mut().insert(path, module.clone());
+        Ok(module)
+    }
+}
+
+fn parse_module(
+    path: &Path,
+    specifier: &str,
+    realm: Option<Realm>,
+    context: &mut Context,
+) -> JsResult<Module> {
+    let source = Source::from_filepath(path).map_err(|e| {
+        JsNativeError::typ().with_message(format!("could not read module '{specifier}': {e}"))
+    })?;
+    Module::parse(source, realm, context)
+}
+
+/// Define the current realm's [`HANDLER_GLOBALS`] as getters reading the
+/// returned holder object.
+fn define_handler_getters(context: &mut Context) -> JsResult<JsObject> {
+    let holder = JsObject::with_null_proto();
+    for name in HANDLER_GLOBALS {
+        let getter = NativeFunction::from_copy_closure_with_captures(
+            move |_this, _args, holder: &JsObject, ctx| holder.get(js_string!(name), ctx),
+            holder.clone(),
+        );
+        let getter = FunctionObjectBuilder::new(context.realm(), getter)
+            .name(js_string!(name))
+            .build();
+        context.global_object().define_property_or_throw(
+            js_string!(name),
+            PropertyDescriptor::builder()
+                .get(getter)
+                .enumerable(false)
+                .configurable(false),
+            context,
+        )?;
+    }
+    Ok(holder)
+}
+
+/// Whether a handler has top-level `import` declarations, and so has to run as
+/// an ES module rather than a classic script.
+pub fn has_import_declarations(script: &str) -> bool {
+    Parser::new(Source::from_bytes(script))
+        .parse_module(&Scope::new_global(), &mut Interner::default())
+        .is_ok_and(|module| {
+            module
+                .items()
+                .items()
+                .iter()
+                .any(|item| matches!(item, ModuleItem::ImportDeclaration(_)))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::has_import_declarations;
+
+    #[test]
+    fn detects_import_declarations() {
+        assert!(has_import_declarations("import { decode } from './jwt.js';\ndecode();"));
+        assert!(has_import_declarations("import './setup.js';"));
+        assert!(!has_import_declarations("const s = `\nimport { a } from \"./a.js\";\n`;"));
+        assert!(!has_import_declarations("import('./lazy.js');"));
+        // Classic scripts that aren't valid modules
+        assert!(!has_import_declarations("with (response) { status; }"));
+    }
+}
@@ -0,0 +1,232 @@
+use boa_engine::{
+    Context, JsNativeError, JsObject, JsResult, JsValue, NativeFunction, js_string,
+    object::ObjectInitializer, object::builtins::JsArray, property::Attribute,
+};
+use serde_json_path::JsonPath;
+use sxd_document::dom::{ChildOfElement, ChildOfRoot, Element};
+use sxd_xpath::{Value as XPathValue, evaluate_xpath};
+
+use crate::js::response::json_to_js;
+
+/// Register the `jsonPath(value, path)` and `xpath(document, expression)` globals.
+pub fn register_query_globals(context: &mut Context) -> JsResult<()> {
+    let json_path_fn = NativeFunction::from_fn_ptr(json_path)
+        .to_js_function(context.realm());
+    context.register_global_property(
+        js_string!("jsonPath"),
+        json_path_fn,
+        Attribute::WRITABLE | Attribute::CONFIGURABLE,
+    )?;
+
+    let xpath_fn = NativeFunction::from_fn_ptr(xpath).to_js_function(context.realm());
+    context.register_global_property(
+        js_string!("xpath"),
+        xpath_fn,
+        Attribute::WRITABLE | Attribute::CONFIGURABLE,
+    )?;
+
+    Ok(())
+}
+
+/// `jsonPath(value, path)` — evaluate an RFC 9535 JSONPath against an object or
+/// a JSON string. Definite paths (no wildcards, slices, unions, filters or
+/// `..`) return the matched value or `undefined`; other paths return an array.
+fn json_path(_this: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let target = args.first().cloned().unwrap_or(JsValue::undefined());
+    let expression = string_arg(args, 1, ctx)?;
+
+    let json = match target.as_string() {
+        Some(text) => serde_json::from_str(&text.to_std_string_escaped()).map_err(|e| {
+            JsNativeError::typ().with_message(format!("jsonPath: value is not valid JSON: {e}"))
+        })?,
+        None => target.to_json(ctx)?.unwrap_or(serde_json::Value::Null),
+    };
+
+    let path = JsonPath::parse(&expression).map_err(|e| {
+        JsNativeError::syntax().with_message(format!("jsonPath: invalid path '{expression}': {e}"))
+    })?;
+    let nodes = path.query(&json).all();
+
+    if is_definite_path(&expression) {
+        return match nodes.first() {
+            Some(value) => json_to_js(value, ctx),
+            None => Ok(JsValue::undefined()),
+        };
+    }
+
+    let arr = JsArray::new(ctx);
+    for value in nodes {
+        arr.push(json_to_js(value, ctx)?, ctx)?;
+    }
+    Ok(arr.into())
+}
+
+/// Whether a JSONPath can match at most one node. Quoted names are ignored.
+pub(crate) fn is_definite_path(expression: &str) -> bool {
+    let mut unquoted = String::new();
+    let mut quote = None;
+    for c in expression.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None => unquoted.push(c),
+        }
+    }
+    !unquoted.contains("..") && !unquoted.contains(['*', '?', ':', ','])
+}
+
+/// `xpath(document, expression)` — evaluate an XPath 1.0 expression against
+/// an XML response body (or an XML string). Node-sets return the string value
+/// of the single matched node, an array for several matches, or `null`.
+fn xpath(_this: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let target = args.first().cloned().unwrap_or(JsValue::undefined());
+    let xml = match target.as_object() {
+        Some(doc) => doc.get(js_string!("xml"), ctx)?,
+        None => target,
+    }
+    .to_string(ctx)?
+    .to_std_string_escaped();
+    let expression = string_arg(args, 1, ctx)?;
+    evaluate_xml_path(&xml, &expression, ctx)
+}
+
+fn evaluate_xml_path(xml: &str, expression: &str, ctx: &mut Context) -> JsResult<JsValue> {
+    let package = sxd_document::parser::parse(xml).map_err(|e| {
+        JsNativeError::typ().with_message(format!("xpath: body is not valid XML: {e}"))
+    })?;
+    let document = package.as_document();
+    let value = evaluate_xpath(&document, expression).map_err(|e| {
+        JsNativeError::syntax().with_message(format!("xpath: cannot evaluate '{expression}': {e}"))
+    })?;
+
+    match value {
+        XPathValue::Boolean(b) => Ok(JsValue::from(b)),
+        XPathValue::Number(n) => Ok(JsValue::from(n)),
+        XPathValue::String(s) => Ok(js_string!(s).into()),
+        XPathValue::Nodeset(nodes) => {
+            let values: Vec<String> = nodes
+                .document_order()
+                .iter()
+                .map(|n| n.string_value())
+                .collect();
+            match values.as_slice() {
+                [] => Ok(JsValue::null()),
+                [single] => Ok(js_string!(single.clone()).into()),
+                _ => {
+                    let arr = JsArray::new(ctx);
+                    for v in values {
+                        arr.push(js_string!(v), ctx)?;
+                    }
+                    Ok(arr.into())
+                }
+            }
+        }
+    }
+}
+
+/// Parse an XML body into a queryable document:
+/// `{ xml, root: { name, attributes, text, children }, xpath(expr), toString() }`.
+/// Returns `None` if the body is not well-formed XML.
+pub fn build_xml_document(xml: &str, context: &mut Context) -> JsResult<Option<JsValue>> {
+    let Ok(package) = sxd_document::parser::parse(xml) else {
+        return Ok(None);
+    };
+    let document = package.as_document();
+    let root_element = document.root().children().into_iter().find_map(|c| match c {
+        ChildOfRoot::Element(e) => Some(e),
+        _ => None,
+    });
+    let root = match root_element {
+        Some(e) => element_to_js(e, context)?.into(),
+        None => JsValue::null(),
+    };
+
+    let xml_owned = xml.to_string();
+    let doc = ObjectInitializer::new(context)
+        .property(js_string!("xml"), js_string!(xml), Attribute::READONLY)
+        .property(js_string!("root"), root, Attribute::READONLY)
+        .function(
+            NativeFunction::from_copy_closure_with_captures(
+                |_this, args, xml, ctx| {
+                    let expression = string_arg(args, 0, ctx)?;
+                    evaluate_xml_path(xml, &expression, ctx)
+                },
+                xml_owned.clone(),
+            ),
+            js_string!("xpath"),
+            1,
+        )
+        .function(
+            NativeFunction::from_copy_closure_with_captures(
+                |_this, _args, xml, _ctx| Ok(js_string!(xml.clone()).into()),
+                xml_owned,
+            ),
+            js_string!("toString"),
+            0,
+        )
+        .build();
+
+    Ok(Some(doc.into()))
+}
+
+fn element_to_js(element: Element<'_>, context: &mut Context) -> JsResult<JsObject> {
+    let attributes = JsObject::with_null_proto();
+    for attr in element.attributes() {
+        attributes.set(
+            js_string!(attr.name().local_part()),
+            js_string!(attr.value()),
+            false,
+            context,
+        )?;
+    }
+
+    let mut text = String::new();
+    let children = JsArray::new(context);
+    for child in element.children() {
+        match child {
+            ChildOfElement::Element(e) => {
+                let child_obj = element_to_js(e, context)?;
+                children.push(child_obj, context)?;
+            }
+            ChildOfElement::Text(t) => text.push_str(t.text()),
+            _ => {}
+        }
+    }
+
+    let obj = ObjectInitializer::new(context)
+        .property(
+            js_string!("name"),
+            js_string!(element.name().local_part()),
+            Attribute::READONLY,
+        )
+        .property(js_string!("attributes"), attributes, Attribute::READONLY)
+        .property(js_string!("text"), js_string!(text.trim()), Attribute::READONLY)
+        .property(js_string!("children"), children, Attribute::READONLY)
+        .build();
+    Ok(obj)
+}
+
+fn string_arg(args: &[JsValue], index: usize, ctx: &mut Context) -> JsResult<String> {
+    Ok(args
+        .get(index)
+        .cloned()
+        .unwrap_or(JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_escaped())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_definite_path;
+
+    #[test]
+    fn definite_paths() {
+        assert!(is_definite_path("$.items[0].id"));
+        assert!(is_definite_path("$['a:b'].c"));
+        assert!(!is_definite_path("$.items[*].id"));
+        assert!(!is_definite_path("$..id"));
+        assert!(!is_definite_path("$.items[?@.active]"));
+        assert!(!is_definite_path("$.items[0:2]"));
+    }
+}
@@ -9,6 +9,7 @@ use boa_engine::{
 };
 
 use crate::http::HttpResponse;
+use crate::js::query::build_xml_document;
 
 /// Build the `response` JS global object from an HttpResponse.
 pub fn build_response_object(
@@ -17,9 +18,19 @@ pub fn build_response_object(
 ) -> JsResult<JsValue> {
     let status = http_response.status as i32;
 
-    // Build body value — either parsed JSON object or raw string
+    // Build body value — parsed JSON object, queryable XML document or raw string
+    let is_xml = http_response
+        .content_type
+        .as_ref()
+        .is_some_and(|ct| ct.mime_type.contains("xml"));
     let body_value = if let Some(json) = &http_response.body_json {
         json_to_js(json, context)?
+    } else if let Some(doc) = is_xml
+        .then(|| build_xml_document(&http_response.body_raw, context))
+        .transpose()?
+        .flatten()
+    {
+        doc
     } else {
         JsValue::from(js_string!(http_response.body_raw.clone()))
     };
@@ -72,7 +83,7 @@ fn build_headers_object(
             NativeFunction::from_copy_closure_with_captures(
                 |_this, args, captures, ctx| {
                     let name = args
-                        .get(0)
+                        .first()
                         .cloned()
                         .unwrap_or(JsValue::undefined())
                         .to_string(ctx)?;
@@ -98,7 +109,7 @@ fn build_headers_object(
             NativeFunction::from_copy_closure_with_captures(
                 |_this, args, captures, ctx| {
                     let name = args
-                        .get(0)
+                        .first()
                         .cloned()
                         .unwrap_or(JsValue::undefined())
                         .to_string(ctx)?;
@@ -134,7 +145,10 @@ pub fn json_to_js(value: &serde_json::Value, context: &mut Context) -> JsResult<
         serde_json::Value::Null => Ok(JsValue::null()),
         serde_json::Value::Bool(b) => Ok(JsValue::from(*b)),
         serde_json::Value::Number(n) => {
-            if let Some(i) = n.as_i64() {
+            // Small integers stay integers so they round-trip through to_json unchanged
+            if let Some(i) = n.as_i64().and_then(|i| i32::try_from(i).ok()) {
+                Ok(JsValue::from(i))
+            } else if let Some(i) = n.as_i64() {
                 Ok(JsValue::from(i as f64))
             } else if let Some(f) = n.as_f64() {
                 Ok(JsValue::from(f))
@@ -1,41 +1,449 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use boa_engine::{Context, Source, js_string, property::Attribute};
+use boa_engine::{
+    Context, JsError, JsNativeError, JsResult, Module, Script, Source,
+    builtins::promise::PromiseState, js_string, object::builtins::JsPromise, property::Attribute,
+};
 
 use crate::error::AppError;
-use crate::http::HttpResponse;
-use crate::js::client::{JsSharedState, build_client_object};
+use crate::http::{self, HttpResponse};
+use crate::js::client::{JsSharedState, TestTracker, build_client_object};
+use crate::js::console::register_console_global;
+use crate::js::crypto::register_crypto_globals;
+use crate::js::event_loop::{EventLoop, LoopExit, register_timer_globals};
+use crate::js::expect::{assertion_diff, is_assertion_error, register_expect_global};
+use crate::js::modules::{ModuleRegistry, has_import_declarations};
+use crate::js::query::register_query_globals;
 use crate::js::response::build_response_object;
+use crate::js::source_map::{HANDLER_SOURCE_PATH, HandlerSourceMap};
+use crate::snapshot::{self, SnapshotSettings};
+use crate::variable::VariableStore;
+
+/// One assertion checked while a test ran.
+#[derive(Debug, Clone)]
+pub struct AssertionRecord {
+    pub passed: bool,
+    pub message: String,
+    /// Line in the `.http` file where the assertion was made, when known.
+    pub line: Option<usize>,
+}
 
 #[derive(Debug, Clone)]
 pub struct TestResult {
     pub name: String,
     pub passed: bool,
-    pub failure_message: Option<String>,
+    /// Every assertion made by the test, in order (including the exception that
+    /// ended it, if any).
+    pub assertions: Vec<AssertionRecord>,
+    pub duration: Duration,
+}
+
+impl TestResult {
+    pub fn pass(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            passed: true,
+            assertions: Vec::new(),
+            duration: Duration::ZERO,
+        }
+    }
+
+    pub fn fail(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            passed: false,
+            assertions: vec![AssertionRecord {
+                passed: false,
+                message: message.into(),
+                line: None,
+            }],
+            duration: Duration::ZERO,
+        }
+    }
+
+    /// Messages of the failed assertions, one per line.
+    pub fn failure_message(&self) -> Option<String> {
+        let failures: Vec<&str> = self
+            .assertions
+            .iter()
+            .filter(|a| !a.passed)
+            .map(|a| a.message.as_str())
+            .collect();
+        (!failures.is_empty()).then(|| failures.join("\n"))
+    }
+
+    /// Line of the first failed assertion whose position is known.
+    pub fn failure_line(&self) -> Option<usize> {
+        self.assertions
+            .iter()
+            .filter(|a| !a.passed)
+            .find_map(|a| a.line)
+    }
+
+    /// Attribute assertions with no known position to `line`.
+    pub fn at_line(mut self, line: usize) -> Self {
+        for assertion in &mut self.assertions {
+            assertion.line.get_or_insert(line);
+        }
+        self
+    }
+}
+
+/// Run-flow requests made by a handler via `client.exit`, `client.skip` and
+/// `client.jump`.
+#[derive(Debug, Clone, Default)]
+pub struct FlowControl {
+    /// Stop the run after this request.
+    pub exit: bool,
+    /// Names of later requests to skip (once each).
+    pub skip: Vec<String>,
+    /// Name of the request to continue the run at.
+    pub jump_to: Option<String>,
+}
+
+/// Severity of a handler log line, from the `console` method that wrote it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    /// `console.log`, `console.table` and `client.log`.
+    Log,
+    Info,
+    Warn,
+    Error,
+    Debug,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: LogLevel,
+    /// May span several lines (pretty-printed objects, tables).
+    pub message: String,
 }
 
 #[derive(Debug)]
 pub struct HandlerResult {
     pub global_vars: HashMap<String, String>,
+    pub removed_globals: HashSet<String>,
     pub test_results: Vec<TestResult>,
-    pub log_output: Vec<String>,
+    pub log_output: Vec<LogLine>,
+    pub flow: FlowControl,
 }
 
-pub fn execute_handler(
-    script: &str,
-    http_response: &HttpResponse,
-    existing_globals: &HashMap<String, String>,
+/// Resource limits applied to every handler execution. There is no memory
+/// limit: boa can't cap its heap, so a handler that keeps allocating is only
+/// stopped by the loop limit.
+#[derive(Debug, Clone)]
+pub struct HandlerLimits {
+    /// Maximum iterations of any single loop.
+    pub loop_iteration_limit: u64,
+    /// Maximum depth of nested function calls.
+    pub recursion_limit: usize,
+    /// Wall-clock budget for the whole handler.
+    pub timeout: Duration,
+}
+
+impl Default for HandlerLimits {
+    fn default() -> Self {
+        Self {
+            loop_iteration_limit: 10_000_000,
+            recursion_limit: 512,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Where a handler script lives in the `.http` file, for error reporting.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptOrigin {
+    /// Path of the `.http` file; relative module specifiers resolve against its directory.
+    pub file: PathBuf,
+    pub request: String,
+    /// Line of the first handler statement.
+    pub line: usize,
+}
+
+// Boa recurses on the native stack for some calls; give it headroom.
+const HANDLER_THREAD_STACK_SIZE: usize = 16 * 1024 * 1024;
+
+/// How long past `limits.timeout` the caller waits before abandoning a
+/// handler, so one that stops at its deadline reports its own timeout.
+const ABANDON_MARGIN: Duration = Duration::from_millis(250);
+
+/// When the running handler has to stop: at its deadline, or as soon as the
+/// caller abandons it. Checked by the event loop and by `client.fetch`.
+#[derive(Debug, Clone)]
+pub struct HandlerDeadline {
+    timeout: Duration,
+    at: Instant,
+    abandoned: Arc<AtomicBool>,
+}
+
+impl HandlerDeadline {
+    fn new(timeout: Duration, abandoned: Arc<AtomicBool>) -> Self {
+        Self {
+            timeout,
+            at: Instant::now() + timeout,
+            abandoned,
+        }
+    }
+
+    /// Time left before the handler has to stop, or `None` once it's up.
+    pub fn remaining(&self) -> Option<Duration> {
+        if self.is_abandoned() {
+            return None;
+        }
+        let remaining = self.at.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    fn is_abandoned(&self) -> bool {
+        self.abandoned.load(Ordering::Relaxed)
+    }
+}
+
+/// A handler waiting to run on the JS worker thread.
+struct HandlerJob {
+    script: String,
+    origin: ScriptOrigin,
+    response: HttpResponse,
+    variables: VariableStore,
+    snapshots: SnapshotSettings,
+    client: http::Client,
+}
+
+struct Worker {
+    jobs: mpsc::Sender<HandlerJob>,
+    results: mpsc::Receiver<Result<HandlerResult, AppError>>,
+    /// Set when the caller stops waiting for the running handler.
+    abandoned: Arc<AtomicBool>,
+}
+
+/// Executes response handlers on a JS thread that lives for the whole run, so
+/// modules loaded by one handler stay loaded (with their state) for the next.
+/// Each handler still gets a fresh realm, and therefore fresh globals.
+///
+/// A handler has `limits.timeout` to finish. If it hasn't reported back shortly
+/// after that (it's stuck in a long synchronous loop, say), it is abandoned
+/// together with its thread. The thread stops at the next timer, promise job or
+/// `client.fetch`, or when it hits the loop or recursion limit. The next
+/// handler starts a new thread with an empty module cache.
+pub struct HandlerRuntime {
+    limits: HandlerLimits,
+    update_snapshots: bool,
+    client: Option<http::Client>,
+    worker: Option<Worker>,
+}
+
+impl HandlerRuntime {
+    pub fn new(limits: HandlerLimits) -> Self {
+        Self {
+            limits,
+            update_snapshots: false,
+            client: None,
+            worker: None,
+        }
+    }
+
+    /// Whether `client.snapshot` overwrites stored snapshots. Where they are
+    /// stored follows from each handler's `.http` file.
+    pub fn with_snapshot_updates(mut self, update: bool) -> Self {
+        self.update_snapshots = update;
+        self
+    }
+
+    /// The HTTP client `client.fetch` sends with, normally the one the
+    /// requests themselves use.
+    pub fn with_client(mut self, client: http::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Start the JS thread now rather than with the first handler, so that
+    /// handler isn't slowed down by building its context.
+    pub fn warm_up(&mut self) -> Result<(), AppError> {
+        if self.worker.is_none() {
+            self.worker = Some(spawn_worker(self.limits.clone())?);
+        }
+        Ok(())
+    }
+
+    /// Run `script` against `http_response`. The handler starts with the
+    /// globals in `variables`; `client.fetch` substitutes all of them.
+    pub fn execute(
+        &mut self,
+        script: &str,
+        origin: &ScriptOrigin,
+        http_response: &HttpResponse,
+        variables: &VariableStore,
+    ) -> Result<HandlerResult, AppError> {
+        let worker = match self.worker.take() {
+            Some(worker) => worker,
+            None => spawn_worker(self.limits.clone())?,
+        };
+
+        let job = HandlerJob {
+            script: script.to_string(),
+            origin: origin.clone(),
+            response: http_response.clone(),
+            variables: variables.clone(),
+            snapshots: SnapshotSettings::new(&origin.file, self.update_snapshots),
+            client: self.client.get_or_insert_with(http::Client::new).clone(),
+        };
+        if worker.jobs.send(job).is_err() {
+            return Err(worker_terminated());
+        }
+
+        match worker.results.recv_timeout(self.limits.timeout + ABANDON_MARGIN) {
+            Ok(result) => {
+                self.worker = Some(worker);
+                result
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                worker.abandoned.store(true, Ordering::Relaxed);
+                Err(AppError::HandlerLimit {
+                    request: origin.request.clone(),
+                    line: origin.line,
+                    message: format!("timed out after {}ms", self.limits.timeout.as_millis()),
+                })
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(worker_terminated()),
+        }
+    }
+}
+
+fn worker_terminated() -> AppError {
+    AppError::JavaScript("handler thread terminated unexpectedly".to_string())
+}
+
+/// Start a JS thread, returning once its context and module realm are ready.
+fn spawn_worker(limits: HandlerLimits) -> Result<Worker, AppError> {
+    let (job_tx, job_rx) = mpsc::channel::<HandlerJob>();
+    let (result_tx, result_rx) = mpsc::channel();
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let abandoned = Arc::new(AtomicBool::new(false));
+    let worker_abandoned = Arc::clone(&abandoned);
+
+    thread::Builder::new()
+        .name("httprun-handler".to_string())
+        .stack_size(HANDLER_THREAD_STACK_SIZE)
+        .spawn(move || {
+            let modules = Rc::new(ModuleRegistry::default());
+            let event_loop = Rc::new(EventLoop::default());
+            let mut context = match Context::builder()
+                .module_loader(Rc::clone(&modules))
+                .job_executor(Rc::clone(&event_loop))
+                .host_hooks(Rc::clone(&event_loop))
+                .build()
+            {
+                Ok(context) => context,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(AppError::JavaScript(format!("{e}"))));
+                    return;
+                }
+            };
+            let runtime_limits = context.runtime_limits_mut();
+            runtime_limits.set_loop_iteration_limit(limits.loop_iteration_limit);
+            runtime_limits.set_recursion_limit(limits.recursion_limit);
+
+            // Modules are evaluated in a realm of their own that lives as long
+            // as the thread, with the helpers that don't depend on the handler.
+            let module_realm = modules.init_realm(&mut context, |context| {
+                register_crypto_globals(context)?;
+                register_query_globals(context)?;
+                register_expect_global(context)?;
+                register_timer_globals(&event_loop, context)?;
+                let require_fn = modules.require_function(None, context);
+                context.register_global_property(
+                    js_string!("require"),
+                    require_fn,
+                    Attribute::READONLY | Attribute::NON_ENUMERABLE,
+                )
+            });
+            if let Err(e) = module_realm {
+                let _ = ready_tx.send(Err(AppError::JavaScript(format!(
+                    "Failed to create the module realm: {e}"
+                ))));
+                return;
+            }
+            let _ = ready_tx.send(Ok(()));
+
+            for job in job_rx {
+                let deadline = HandlerDeadline::new(limits.timeout, Arc::clone(&worker_abandoned));
+                let result = run_handler(&mut context, &modules, &event_loop, deadline, &job);
+                // The receiver is gone if the handler timed out.
+                if result_tx.send(result).is_err() {
+                    break;
+                }
+            }
+        })?;
+    ready_rx.recv().map_err(|_| worker_terminated())??;
+
+    Ok(Worker {
+        jobs: job_tx,
+        results: result_rx,
+        abandoned,
+    })
+}
+
+/// Run one handler in a fresh realm of the worker's context.
+fn run_handler(
+    context: &mut Context,
+    modules: &Rc<ModuleRegistry>,
+    event_loop: &Rc<EventLoop>,
+    deadline: HandlerDeadline,
+    job: &HandlerJob,
 ) -> Result<HandlerResult, AppError> {
-    let mut context = Context::default();
+    let realm = context
+        .create_realm()
+        .map_err(|e| AppError::JavaScript(format!("Failed to create realm: {e}")))?;
+    let previous = context.enter_realm(realm);
+    let result = run_in_realm(context, modules, event_loop, deadline, job);
+    event_loop.clear();
+    context.enter_realm(previous);
+    result
+}
+
+fn run_in_realm(
+    context: &mut Context,
+    modules: &Rc<ModuleRegistry>,
+    event_loop: &Rc<EventLoop>,
+    deadline: HandlerDeadline,
+    job: &HandlerJob,
+) -> Result<HandlerResult, AppError> {
+    let HandlerJob {
+        script,
+        origin,
+        response: http_response,
+        variables,
+        snapshots,
+        client,
+    } = job;
+
+    let base_dir = origin.file.parent().unwrap_or(Path::new(""));
+    modules.set_base_dir(base_dir);
+
+    let source_map = HandlerSourceMap::new(script, origin);
     let shared_state = Rc::new(RefCell::new(JsSharedState {
-        global_vars: existing_globals.clone(),
+        global_vars: variables.globals().clone(),
+        variables: variables.clone(),
+        http_client: Some(client.clone()),
+        source_map: source_map.clone(),
+        base_dir: base_dir.to_path_buf(),
+        snapshots: snapshots.clone(),
+        response_body: snapshot::body_value(http_response),
+        tests: TestTracker::with_context(event_loop.test_context()),
+        deadline: Some(deadline.clone()),
         ..Default::default()
     }));
 
     // Build and register `response` global
-    let response_obj = build_response_object(http_response, &mut context)
+    let response_obj = build_response_object(http_response, context)
         .map_err(|e| AppError::JavaScript(format!("Failed to build response object: {e}")))?;
     context
         .register_global_property(
@@ -46,7 +454,7 @@ pub fn execute_handler(
         .map_err(|e| AppError::JavaScript(format!("{e}")))?;
 
     // Build and register `client` global
-    let client_obj = build_client_object(Rc::clone(&shared_state), &mut context)
+    let client_obj = build_client_object(Rc::clone(&shared_state), context)
         .map_err(|e| AppError::JavaScript(format!("Failed to build client object: {e}")))?;
     context
         .register_global_property(
@@ -56,23 +464,184 @@ pub fn execute_handler(
         )
         .map_err(|e| AppError::JavaScript(format!("{e}")))?;
 
-    // Execute the handler script
+    // Register `console`
+    register_console_global(Rc::clone(&shared_state), context)
+        .map_err(|e| AppError::JavaScript(format!("Failed to register console: {e}")))?;
+
+    // Register `crypto` and `jwt` helpers
+    register_crypto_globals(context)
+        .map_err(|e| AppError::JavaScript(format!("Failed to register crypto helpers: {e}")))?;
+
+    // Register `jsonPath` and `xpath` query helpers
+    register_query_globals(context)
+        .map_err(|e| AppError::JavaScript(format!("Failed to register query helpers: {e}")))?;
+
+    // Register the `expect` assertion library
+    register_expect_global(context)
+        .map_err(|e| AppError::JavaScript(format!("Failed to register expect: {e}")))?;
+
+    // Register `setTimeout`/`setInterval` and their `clear*` counterparts
+    register_timer_globals(event_loop, context)
+        .map_err(|e| AppError::JavaScript(format!("Failed to register timers: {e}")))?;
+
+    // Register CommonJS `require`
+    let require_fn = modules.require_function(None, context);
     context
-        .eval(Source::from_bytes(script))
+        .register_global_property(
+            js_string!("require"),
+            require_fn,
+            Attribute::READONLY | Attribute::NON_ENUMERABLE,
+        )
+        .map_err(|e| AppError::JavaScript(format!("{e}")))?;
+
+    // Modules see this handler's `client`, `response` and `console`
+    modules
+        .bind_handler_globals(context)
         .map_err(|e| AppError::JavaScript(format!("{e}")))?;
 
+    // Execute the handler, then run promise jobs and timers until it is idle.
+    let result = start_handler(script, &shared_state, context).and_then(|handler_promise| {
+        event_loop.run(
+            context,
+            deadline.at,
+            || {
+                let state = shared_state.borrow();
+                state.flow.exit || state.unwinding.is_some() || deadline.is_abandoned()
+            },
+            || {
+                shared_state.borrow().tests.has_pending()
+                    || handler_promise.as_ref().is_some_and(|p| matches!(p.state(), PromiseState::Pending))
+            },
+        )
+    });
+    let timed_out = || AppError::HandlerLimit {
+        request: origin.request.clone(),
+        line: origin.line,
+        message: format!("timed out after {}ms", deadline.timeout.as_millis()),
+    };
+    match result {
+        Ok(LoopExit::Idle) => {}
+        Ok(LoopExit::Deadline) => return Err(timed_out()),
+        Err(e) => report_handler_error(e, &shared_state, origin, context)?,
+    }
+    // A module handler stopped by client.exit() or a runtime limit ended with
+    // a plain error in its place; report what actually stopped it.
+    let unwinding = shared_state.borrow_mut().unwinding.take();
+    if let Some(error) = unwinding {
+        report_handler_error(error, &shared_state, origin, context)?;
+    }
+
+    // A promise rejected without a handler (an async function nobody awaited)
+    // is reported like an error thrown by the handler itself, unless the
+    // handler called client.exit() and nothing after that matters.
+    let rejections = event_loop.take_unhandled_rejections();
+    for promise in rejections.into_iter().filter(|_| !shared_state.borrow().flow.exit) {
+        if let Ok(promise) = JsPromise::from_object(promise)
+            && let PromiseState::Rejected(reason) = promise.state()
+        {
+            report_handler_error(JsError::from_opaque(reason), &shared_state, origin, context)?;
+        }
+    }
+
+    let test_results = shared_state.borrow_mut().tests.finish();
     let state = shared_state.borrow();
     Ok(HandlerResult {
         global_vars: state.global_vars.clone(),
-        test_results: state.test_results.clone(),
+        removed_globals: state.removed_globals.clone(),
+        test_results,
         log_output: state.log_output.clone(),
+        flow: state.flow.clone(),
     })
 }
 
+/// Start running a handler. One with `import` declarations is evaluated as an
+/// ES module, whose imports are loaded through the worker's `ModuleRegistry`;
+/// any other runs as a classic script. Returns the promise to wait for: the
+/// module's evaluation, or the script's completion value if it is a promise
+/// (an async IIFE, say), which is waited on like an async test.
+fn start_handler(
+    script: &str,
+    shared_state: &RefCell<JsSharedState>,
+    context: &mut Context,
+) -> JsResult<Option<JsPromise>> {
+    let source = || Source::from_bytes(script).with_path(Path::new(HANDLER_SOURCE_PATH));
+    let parsed = match Script::parse(source(), None, context) {
+        Ok(parsed) => parsed,
+        Err(_) if has_import_declarations(script) => {
+            let module = Module::parse(source(), None, context)?;
+            shared_state.borrow_mut().in_module = true;
+            return Ok(Some(module.load_link_evaluate(context)));
+        }
+        Err(e) => return Err(e),
+    };
+    let completion = parsed.evaluate(context)?;
+    Ok(completion.as_object().and_then(|o| JsPromise::from_object(o.clone()).ok()))
+}
+
+/// Handle an error that escaped the handler. An error raised by client.exit()
+/// is not a failure, and a failed top-level `expect` fails the implicit test
+/// rather than the handler.
+fn report_handler_error(
+    error: JsError,
+    shared_state: &Rc<RefCell<JsSharedState>>,
+    origin: &ScriptOrigin,
+    context: &mut Context,
+) -> Result<(), AppError> {
+    if is_exit_signal(&error) {
+        return Ok(());
+    }
+    if is_runtime_limit(&error) {
+        let state = shared_state.borrow();
+        return Err(AppError::HandlerLimit {
+            request: origin.request.clone(),
+            line: state.source_map.error_line(&error).unwrap_or(origin.line),
+            message: state.source_map.describe_error(&error),
+        });
+    }
+    let mut message = shared_state.borrow().source_map.describe_error(&error);
+    if let Some(diff) = assertion_diff(&error, context) {
+        message.push_str(&diff);
+    }
+    let line = shared_state.borrow().source_map.error_line(&error);
+    if !is_assertion_error(&error, context) {
+        return Err(AppError::HandlerException { message, line });
+    }
+    shared_state.borrow_mut().tests.record_assertion(AssertionRecord {
+        passed: false,
+        message,
+        line,
+    });
+    Ok(())
+}
+
+/// Message of the error `client.exit()` unwinds the handler with. It is a
+/// runtime-limit error, the only kind `try`/`catch` can't stop. Scripts can't
+/// raise those themselves, so the message tells it apart from a real limit.
+const EXIT_SIGNAL: &str = "client.exit() called";
+
+/// The error `client.exit()` throws to stop the handler.
+pub(crate) fn exit_signal() -> JsError {
+    JsNativeError::runtime_limit().with_message(EXIT_SIGNAL).into()
+}
+
+/// Whether `error` was thrown by `client.exit()`.
+pub(crate) fn is_exit_signal(error: &JsError) -> bool {
+    error
+        .as_native()
+        .is_some_and(|e| e.is_runtime_limit() && e.message() == EXIT_SIGNAL)
+}
+
+/// Whether `error` was raised by boa's runtime limits. These cannot be caught by
+/// scripts and must not be swallowed by `client.test` either.
+pub(crate) fn is_runtime_limit(error: &JsError) -> bool {
+    error.as_native().is_some_and(|e| e.is_runtime_limit()) && !is_exit_signal(error)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::http::{ContentType, HttpResponse};
+    use crate::js::client::IMPLICIT_TEST_NAME;
 
     fn dummy_response() -> HttpResponse {
         HttpResponse {
@@ -88,12 +657,25 @@ mod tests {
         }
     }
 
+    fn run(
+        script: &str,
+        resp: &HttpResponse,
+        globals: &HashMap<String, String>,
+    ) -> Result<HandlerResult, AppError> {
+        HandlerRuntime::new(HandlerLimits::default()).execute(
+            script,
+            &ScriptOrigin::default(),
+            resp,
+            &VariableStore::default().with_globals(globals.clone()),
+        )
+    }
+
     #[test]
     fn global_vars_persist_across_handler_calls() {
         // First handler sets a global variable
         let script1 = r#"client.global.set("totalElements", response.body.totalElements);"#;
         let resp = dummy_response();
-        let result1 = execute_handler(script1, &resp, &HashMap::new()).unwrap();
+        let result1 = run(script1, &resp, &HashMap::new()).unwrap();
         assert_eq!(result1.global_vars.get("totalElements").unwrap(), "12");
 
         // Second handler reads the global variable set by the first
@@ -103,7 +685,7 @@ mod tests {
                 client.assert(expected === 12, "expected 12 but got " + expected);
             });
         "#;
-        let result2 = execute_handler(script2, &resp, &result1.global_vars).unwrap();
+        let result2 = run(script2, &resp, &result1.global_vars).unwrap();
         assert!(
             result2.test_results.iter().all(|r| r.passed),
             "test failed: {:?}",
@@ -120,7 +702,7 @@ mod tests {
             });
         "#;
         let resp = dummy_response();
-        let result = execute_handler(script, &resp, &HashMap::new()).unwrap();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
         assert!(result.test_results.iter().all(|r| r.passed));
     }
 
@@ -155,11 +737,885 @@ mod tests {
             });
         "#;
         let resp = dummy_response();
-        let result = execute_handler(script, &resp, &HashMap::new()).unwrap();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
         assert!(
             result.test_results.iter().all(|r| r.passed),
             "test failed: {:?}",
             result.test_results,
         );
     }
+
+    #[test]
+    fn global_clear_reports_removed_names() {
+        let script = r#"
+            client.global.set("keep", "1");
+            client.global.clear("token");
+            client.global.clear("keep");
+            client.global.set("keep", "2");
+        "#;
+        let resp = dummy_response();
+        let mut existing = HashMap::new();
+        existing.insert("token".to_string(), "abc".to_string());
+        let result = run(script, &resp, &existing).unwrap();
+        assert!(!result.global_vars.contains_key("token"));
+        assert!(result.removed_globals.contains("token"));
+        assert_eq!(result.global_vars.get("keep").unwrap(), "2");
+        assert!(!result.removed_globals.contains("keep"));
+    }
+
+    #[test]
+    fn global_clear_all_and_is_empty() {
+        let script = r#"
+            client.test("Not empty before clearAll", function() {
+                client.assert(!client.global.isEmpty(), "expected globals");
+            });
+            client.global.clearAll();
+            client.test("Empty after clearAll", function() {
+                client.assert(client.global.isEmpty(), "expected no globals");
+            });
+        "#;
+        let resp = dummy_response();
+        let mut existing = HashMap::new();
+        existing.insert("a".to_string(), "1".to_string());
+        existing.insert("b".to_string(), "2".to_string());
+        let result = run(script, &resp, &existing).unwrap();
+        assert!(
+            result.test_results.iter().all(|r| r.passed),
+            "test failed: {:?}",
+            result.test_results,
+        );
+        assert!(result.global_vars.is_empty());
+        assert_eq!(result.removed_globals.len(), 2);
+    }
+
+    #[test]
+    fn exit_stops_handler_without_error() {
+        let script = r#"
+            client.test("Before exit", function() {
+                client.assert(true);
+            });
+            client.test("Exit inside test", function() {
+                client.exit();
+            });
+            client.global.set("unreachable", "1");
+        "#;
+        let resp = dummy_response();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        assert!(result.flow.exit);
+        assert_eq!(result.test_results.len(), 1);
+        assert!(!result.global_vars.contains_key("unreachable"));
+    }
+
+    #[test]
+    fn exit_cannot_be_caught() {
+        let script = r#"
+            try {
+                client.exit();
+            } catch (e) {
+                client.log("caught");
+            } finally {
+                client.global.set("finally", "1");
+            }
+            client.global.set("after_exit", "1");
+        "#;
+        let resp = dummy_response();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        assert!(result.flow.exit);
+        assert!(result.global_vars.is_empty(), "{:?}", result.global_vars);
+        assert!(result.log_output.is_empty());
+
+        let script = r#"
+            client.test("exits after await", async () => {
+                await null;
+                try { client.exit(); } catch (e) {}
+                client.global.set("after_exit", "1");
+            });
+        "#;
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        assert!(result.flow.exit);
+        assert!(result.global_vars.is_empty(), "{:?}", result.global_vars);
+    }
+
+    #[test]
+    fn exit_is_not_a_runtime_limit() {
+        assert!(is_exit_signal(&exit_signal()));
+        assert!(!is_runtime_limit(&exit_signal()));
+        let limit: JsError = JsNativeError::runtime_limit().with_message("loop limit").into();
+        assert!(is_runtime_limit(&limit));
+        assert!(!is_exit_signal(&limit));
+    }
+
+    #[test]
+    fn skip_and_jump_are_reported() {
+        let script = r#"
+            client.skip("cleanup");
+            client.jump("login");
+        "#;
+        let resp = dummy_response();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        assert!(!result.flow.exit);
+        assert_eq!(result.flow.skip, vec!["cleanup".to_string()]);
+        assert_eq!(result.flow.jump_to.as_deref(), Some("login"));
+    }
+
+    #[test]
+    fn infinite_loop_hits_loop_limit() {
+        let limits = HandlerLimits {
+            loop_iteration_limit: 1_000,
+            ..Default::default()
+        };
+        let origin = ScriptOrigin {
+            request: "spin".to_string(),
+            line: 7,
+            ..Default::default()
+        };
+        let script = r#"
+            client.test("spins", function() {
+                while (true) {}
+            });
+        "#;
+        let resp = dummy_response();
+        let err = HandlerRuntime::new(limits)
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap_err();
+        match err {
+            AppError::HandlerLimit { request, line, message } => {
+                assert_eq!(request, "spin");
+                // The test callback on the handler's second line
+                assert_eq!(line, 8);
+                assert!(!message.contains(HANDLER_SOURCE_PATH), "{message}");
+            }
+            other => panic!("expected HandlerLimit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn runaway_recursion_hits_recursion_limit() {
+        let origin = ScriptOrigin {
+            file: PathBuf::from("api.http"),
+            request: "deep".to_string(),
+            line: 4,
+        };
+        let script = "var depth = 0;\nfunction f() { return f(); }\nf();";
+        let resp = dummy_response();
+        let err = HandlerRuntime::new(HandlerLimits::default())
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap_err();
+        match err {
+            AppError::HandlerLimit { line, message, .. } => {
+                assert_eq!(line, 5);
+                assert!(message.contains("--> api.http:5:"), "{message}");
+                assert_eq!(message.matches("at f (api.http:5:").count(), 1, "{message}");
+                assert!(message.contains("previous frame repeated"), "{message}");
+            }
+            other => panic!("expected HandlerLimit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn slow_handler_times_out() {
+        let limits = HandlerLimits {
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let script = "for (var i = 0; i < 5000000; i++) {} for (var j = 0; j < 5000000; j++) {}";
+        let resp = dummy_response();
+        let err = HandlerRuntime::new(limits)
+            .execute(script, &ScriptOrigin::default(), &resp, &VariableStore::default())
+            .unwrap_err();
+        match err {
+            AppError::HandlerLimit { message, .. } => assert!(message.contains("timed out")),
+            other => panic!("expected HandlerLimit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn fetch_stops_at_the_handler_deadline() {
+        let limits = HandlerLimits {
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        // Busy past the deadline (but within the caller's margin), then fetch
+        let script = r#"
+            const start = Date.now();
+            while (Date.now() - start < 100) {}
+            try { client.fetch("GET", "http://127.0.0.1:9/"); } catch (e) {}
+        "#;
+        let resp = dummy_response();
+        let err = HandlerRuntime::new(limits)
+            .execute(script, &ScriptOrigin::default(), &resp, &VariableStore::default())
+            .unwrap_err();
+        match err {
+            AppError::HandlerLimit { message, .. } => assert!(message.contains("out of time"), "{message}"),
+            other => panic!("expected HandlerLimit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_exception_includes_mapped_stack_trace() {
+        let origin = ScriptOrigin {
+            file: PathBuf::from("api.http"),
+            request: "items".to_string(),
+            line: 20,
+        };
+        let script = "client.test(\"reads missing field\", function() {\n  response.body.missing.id;\n});";
+        let resp = dummy_response();
+        let result = HandlerRuntime::new(HandlerLimits::default())
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap();
+        let message = result.test_results[0].failure_message().unwrap();
+        assert!(message.starts_with("Exception: TypeError"), "{message}");
+        assert!(message.contains("api.http:21:"), "{message}");
+        assert!(message.contains("at test (native)"), "{message}");
+    }
+
+    #[test]
+    fn handler_exception_carries_its_http_line() {
+        let origin = ScriptOrigin {
+            file: PathBuf::from("api.http"),
+            request: "items".to_string(),
+            line: 20,
+        };
+        let script = "var items = response.body.items;\nitems.length;";
+        let resp = dummy_response();
+        let err = HandlerRuntime::new(HandlerLimits::default())
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap_err();
+        assert!(matches!(&err, AppError::HandlerException { .. }), "got {err:?}");
+        assert_eq!(err.handler_line(), Some(21));
+    }
+
+    fn module_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("httprun-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn es_modules_are_evaluated_once_per_runtime() {
+        let dir = module_dir("esm");
+        std::fs::write(
+            dir.join("lib/counter.js"),
+            "import { step } from './step.js';\nlet count = 0;\nexport function next() { count += step; return count; }",
+        )
+        .unwrap();
+        std::fs::write(dir.join("lib/step.js"), "export const step = 1;").unwrap();
+
+        let origin = ScriptOrigin {
+            file: dir.join("api.http"),
+            ..Default::default()
+        };
+        let script = r#"
+            import { next } from "./lib/counter.js";
+            client.global.set("count", next());
+        "#;
+        let resp = dummy_response();
+        let mut runtime = HandlerRuntime::new(HandlerLimits::default());
+        let first = runtime.execute(script, &origin, &resp, &VariableStore::default()).unwrap();
+        let variables = VariableStore::default().with_globals(first.global_vars.clone());
+        let second = runtime.execute(script, &origin, &resp, &variables).unwrap();
+        assert_eq!(first.global_vars.get("count").unwrap(), "1");
+        assert_eq!(second.global_vars.get("count").unwrap(), "2");
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn module_helpers_use_the_running_handler() {
+        let dir = module_dir("helpers");
+        std::fs::write(
+            dir.join("lib/checks.js"),
+            "export function statusOk() {\n  client.test('status ok', () => client.assert(response.status === 200));\n}",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("lib/log.js"),
+            "module.exports = function (message) { console.log(message + ' ' + response.status); };",
+        )
+        .unwrap();
+
+        let origin = ScriptOrigin {
+            file: dir.join("api.http"),
+            ..Default::default()
+        };
+        let script = r#"
+            import { statusOk } from "./lib/checks.js";
+            statusOk();
+            require("./lib/log.js")("got");
+        "#;
+        let mut runtime = HandlerRuntime::new(HandlerLimits::default());
+        let ok = dummy_response();
+        let failing = HttpResponse {
+            status: 500,
+            ..dummy_response()
+        };
+        let results: Vec<_> = [&ok, &failing]
+            .into_iter()
+            .map(|resp| runtime.execute(script, &origin, resp, &VariableStore::default()).unwrap())
+            .collect();
+
+        let tests = |result: &HandlerResult| -> Vec<(String, bool)> {
+            result.test_results.iter().map(|t| (t.name.clone(), t.passed)).collect()
+        };
+        assert_eq!(tests(&results[0]), [("status ok".to_string(), true)]);
+        assert_eq!(tests(&results[1]), [("status ok".to_string(), false)]);
+        assert_eq!(results[0].log_output[0].message, "got 200");
+        assert_eq!(results[1].log_output[0].message, "got 500");
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn require_loads_commonjs_modules() {
+        let dir = module_dir("cjs");
+        std::fs::write(
+            dir.join("lib/jwt.js"),
+            "var calls = 0;\nmodule.exports = { claims: function (s) { calls++; return s.split('.').length + calls; } };",
+        )
+        .unwrap();
+
+        let origin = ScriptOrigin {
+            file: dir.join("api.http"),
+            ..Default::default()
+        };
+        let script = r#"
+            var jwt = require("./lib/jwt");
+            client.test("shared helper", function() {
+                client.assert(jwt.claims("a.b.c") === 4, "first call");
+                client.assert(require("./lib/jwt.js") === jwt, "cached");
+            });
+        "#;
+        let resp = dummy_response();
+        let result = HandlerRuntime::new(HandlerLimits::default())
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap();
+        assert!(
+            result.test_results.iter().all(|r| r.passed),
+            "test failed: {:?}",
+            result.test_results,
+        );
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn handler_globals_do_not_leak_between_handlers() {
+        let resp = dummy_response();
+        let mut runtime = HandlerRuntime::new(HandlerLimits::default());
+        runtime
+            .execute("var leaked = 1;", &ScriptOrigin::default(), &resp, &VariableStore::default())
+            .unwrap();
+        let script = r#"
+            client.test("fresh realm", function() {
+                client.assert(typeof leaked === "undefined", "global leaked");
+            });
+        "#;
+        let result = runtime
+            .execute(script, &ScriptOrigin::default(), &resp, &VariableStore::default())
+            .unwrap();
+        assert!(result.test_results.iter().all(|r| r.passed));
+    }
+
+    #[test]
+    fn loop_limit_in_module_handler() {
+        let dir = module_dir("esm-limit");
+        std::fs::write(dir.join("lib/noop.js"), "export const x = 1;").unwrap();
+        let limits = HandlerLimits {
+            loop_iteration_limit: 1_000,
+            ..Default::default()
+        };
+        let origin = ScriptOrigin {
+            file: dir.join("api.http"),
+            ..Default::default()
+        };
+        let script = "import { x } from './lib/noop.js';\nclient.test('spins', function() { while (x) {} });";
+        let resp = dummy_response();
+        let mut runtime = HandlerRuntime::new(limits);
+        let err = runtime
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap_err();
+        assert!(matches!(err, AppError::HandlerLimit { line: 2, .. }), "got {err:?}");
+
+        // Nothing can stop a limit hit by the module's own code from ending
+        // its thread; the next handler gets a new one
+        let script = "import { x } from './lib/noop.js';\nwhile (x) {}";
+        let err = runtime
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap_err();
+        assert!(matches!(err, AppError::JavaScript(_)), "got {err:?}");
+
+        // client.exit() ends a module handler quietly, from a test too
+        let script = "import { x } from './lib/noop.js';\nclient.global.set('x', x);\nclient.exit();\nclient.global.set('x', 2);";
+        let result = runtime
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap();
+        assert!(result.flow.exit);
+        assert_eq!(result.global_vars.get("x").unwrap(), "1");
+
+        let script = "import { x } from './lib/noop.js';\nclient.global.set('x', x);\nclient.test('stops', function() { client.exit(); });\nclient.global.set('x', 2);";
+        let result = runtime
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap();
+        assert!(result.flow.exit);
+        assert!(result.test_results.is_empty(), "got {:?}", result.test_results);
+        assert_eq!(result.global_vars.get("x").unwrap(), "1");
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn import_handlers_can_require_commonjs_modules() {
+        let dir = module_dir("esm-cjs");
+        std::fs::write(dir.join("lib/jwt.js"), "export function decode(t) { return t.split('.').length; }").unwrap();
+        std::fs::write(
+            dir.join("lib/paging.js"),
+            "let pages = 0;\nvar size = 10;\nmodule.exports = { next: function () { pages++; return pages * size; } };",
+        )
+        .unwrap();
+        let origin = ScriptOrigin {
+            file: dir.join("api.http"),
+            ..Default::default()
+        };
+        let script = r#"
+            import { decode } from "./lib/jwt.js";
+            const paging = require("./lib/paging.js");
+            client.global.set("result", `${decode("a.b.c")},${paging.next()}`);
+        "#;
+        let resp = dummy_response();
+        let result = HandlerRuntime::new(HandlerLimits::default())
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap();
+        assert_eq!(result.global_vars.get("result").unwrap(), "3,10");
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn import_handlers_run_as_modules() {
+        let dir = module_dir("esm-handler");
+        std::fs::write(
+            dir.join("lib/counter.js"),
+            "export let count = 0;\nexport function bump() { count++; }",
+        )
+        .unwrap();
+        let origin = ScriptOrigin {
+            file: dir.join("api.http"),
+            ..Default::default()
+        };
+        let script = r#"
+            import { count, bump } from "./lib/counter.js";
+            const text = `
+import { missing } from "./lib/missing.js";
+`;
+            const before = count;
+            bump();
+            await null;
+            client.global.set("counts", `${before},${count}`);
+            client.global.set("text", text.trim());
+        "#;
+        let resp = dummy_response();
+        let mut runtime = HandlerRuntime::new(HandlerLimits::default());
+        let result = runtime
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap();
+        // Imports are live bindings, and strings are left alone
+        assert_eq!(result.global_vars.get("counts").unwrap(), "0,1");
+        assert_eq!(
+            result.global_vars.get("text").unwrap(),
+            r#"import { missing } from "./lib/missing.js";"#
+        );
+
+        let script = "import { nope } from './lib/counter.js';";
+        let err = runtime
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap_err();
+        assert!(matches!(&err, AppError::HandlerException { message, .. } if message.contains("nope")), "got {err:?}");
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn json_path_queries() {
+        let mut resp = dummy_response();
+        resp.body_raw = r#"{"items": [{"id": 1, "active": true}, {"id": 2, "active": false}]}"#.to_string();
+        resp.body_json = serde_json::from_str(&resp.body_raw).ok();
+        let script = r#"
+            client.test("jsonPath", function() {
+                client.assert(jsonPath(response.body, "$.items[1].id") === 2, "definite path");
+                client.assert(jsonPath(response.body, "$.missing") === undefined, "no match");
+                var ids = jsonPath(response.body, "$.items[?@.active == true].id");
+                client.assert(ids.length === 1 && ids[0] === 1, "filter returns array");
+                client.assert(jsonPath('{"a": [5]}', "$.a[0]") === 5, "string input");
+            });
+        "#;
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        assert!(
+            result.test_results.iter().all(|r| r.passed),
+            "{:?}",
+            result.test_results
+        );
+    }
+
+    #[test]
+    fn xml_body_is_queryable() {
+        let mut resp = dummy_response();
+        resp.body_raw =
+            r#"<order id="7"><item sku="a">Apple</item><item sku="b">Pear</item></order>"#.to_string();
+        resp.body_json = None;
+        resp.content_type = Some(ContentType {
+            mime_type: "application/xml".to_string(),
+            charset: None,
+        });
+        let script = r#"
+            client.test("xml", function() {
+                client.assert(response.body.root.name === "order", "root name");
+                client.assert(response.body.root.attributes.id === "7", "root attribute");
+                client.assert(response.body.root.children[1].text === "Pear", "child text");
+                client.assert(response.body.xpath("/order/@id") === "7", "xpath method");
+                client.assert(xpath(response.body, "count(//item)") === 2, "xpath count");
+                client.assert(xpath(response.body, "//item").length === 2, "node-set array");
+                client.assert(String(response.body).indexOf("<order") === 0, "toString");
+            });
+        "#;
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        assert!(
+            result.test_results.iter().all(|r| r.passed),
+            "{:?}",
+            result.test_results
+        );
+    }
+
+    #[test]
+    fn expect_assertions() {
+        let script = r#"
+            client.test("passes", function() {
+                expect(response).to.have.status(200);
+                expect(response.body).to.have.property("totalElements", 12);
+                expect([1, 2, 3]).to.include(2).and.not.include(4);
+                expect("abc-123").to.match(/\d+$/);
+                expect({ a: [1] }).to.deep.equal({ a: [1] });
+            });
+            client.test("status", function() {
+                expect(response).to.have.status(404);
+            });
+            client.test("diff", function() {
+                expect({ id: 1, name: "a" }).to.eql({ id: 2, name: "a" });
+            });
+        "#;
+        let resp = dummy_response();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        let [passes, status, diff] = result.test_results.as_slice() else {
+            panic!("unexpected results: {:?}", result.test_results);
+        };
+        assert!(passes.passed, "{passes:?}");
+
+        let status = status.failure_message().unwrap();
+        assert!(
+            status.starts_with("AssertionError: expected response to have status 404 but got 200"),
+            "{status}"
+        );
+
+        let diff = diff.failure_message().unwrap();
+        assert!(diff.contains("-   \"id\": 2,"), "{diff}");
+        assert!(diff.contains("+   \"id\": 1,"), "{diff}");
+        assert!(!diff.contains("Exception"), "{diff}");
+    }
+
+    #[test]
+    fn validate_schema_records_violations() {
+        let dir = module_dir("schema");
+        std::fs::write(
+            dir.join("lib/page.json"),
+            r#"{ "type": "object", "properties": { "totalElements": { "type": "string" } } }"#,
+        )
+        .unwrap();
+        let origin = ScriptOrigin {
+            file: dir.join("api.http"),
+            ..Default::default()
+        };
+        let script = r#"
+            client.test("page schema", function() {
+                client.assert(!client.validateSchema(response.body, "./lib/page.json"), "should be invalid");
+            });
+            client.assert(client.validateSchema({ totalElements: "12" }, "./lib/page.json"), "should be valid");
+        "#;
+        let resp = dummy_response();
+        let result = HandlerRuntime::new(HandlerLimits::default())
+            .execute(script, &origin, &resp, &VariableStore::default())
+            .unwrap();
+        let [page, implicit] = result.test_results.as_slice() else {
+            panic!("unexpected results: {:?}", result.test_results);
+        };
+        assert!(!page.passed);
+        assert_eq!(
+            page.failure_message().unwrap(),
+            "Schema ./lib/page.json at /totalElements: 12 is not of type \"string\""
+        );
+        assert!(implicit.passed, "{implicit:?}");
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn top_level_asserts_roll_into_implicit_test() {
+        let script = r#"
+            client.assert(response.status === 200, "status is 200");
+            client.test("two failures", function() {
+                client.assert(false, "first");
+                client.assert(true, "second");
+                client.assert(false, "third");
+            });
+            client.assert(response.body.totalElements === 12, "has 12 elements");
+            expect(response.body.totalElements).to.be.above(20);
+        "#;
+        let resp = dummy_response();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        let [test, implicit] = result.test_results.as_slice() else {
+            panic!("unexpected results: {:?}", result.test_results);
+        };
+
+        assert_eq!(test.name, "two failures");
+        assert!(!test.passed);
+        assert_eq!(test.assertions.len(), 3);
+        assert_eq!(test.failure_message().unwrap(), "first\nthird");
+
+        assert_eq!(implicit.name, IMPLICIT_TEST_NAME);
+        assert!(!implicit.passed);
+        assert_eq!(implicit.assertions.len(), 3);
+        assert!(implicit.assertions[0].passed && implicit.assertions[1].passed);
+        assert!(
+            implicit.assertions[2]
+                .message
+                .starts_with("AssertionError: expected 12 to be above 20"),
+            "{implicit:?}"
+        );
+    }
+
+    #[test]
+    fn handler_with_only_passing_asserts_reports_a_pass() {
+        let script = r#"client.assert(response.status === 200, "status is 200");"#;
+        let resp = dummy_response();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        assert_eq!(result.test_results.len(), 1);
+        assert!(result.test_results[0].passed);
+    }
+
+    #[test]
+    fn async_tests_wait_for_promises_and_timers() {
+        let script = r#"
+            client.test("polls until ready", async () => {
+                let polls = 0;
+                await new Promise(resolve => setTimeout(resolve, 5));
+                const id = setInterval(() => {
+                    polls++;
+                    if (polls === 3) clearInterval(id);
+                }, 1);
+                await new Promise(resolve => setTimeout(resolve, 30));
+                client.assert(polls === 3, "polled three times");
+            });
+            client.test("rejects after await", async () => {
+                await null;
+                expect(1).to.equal(2);
+            });
+            client.test("never settles", () => new Promise(() => {}));
+            Promise.resolve("later").then(v => client.global.set("continuation", v));
+        "#;
+        let resp = dummy_response();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        let by_name = |name: &str| {
+            result
+                .test_results
+                .iter()
+                .find(|t| t.name == name)
+                .unwrap_or_else(|| panic!("no test {name}: {:?}", result.test_results))
+        };
+
+        let polled = by_name("polls until ready");
+        assert!(polled.passed, "{polled:?}");
+        assert_eq!(polled.assertions.len(), 1);
+        let rejected = by_name("rejects after await");
+        assert!(
+            rejected
+                .failure_message()
+                .unwrap()
+                .starts_with("AssertionError: expected 1 to equal 2"),
+            "{rejected:?}"
+        );
+        assert!(by_name("never settles").failure_message().unwrap().contains("never settled"));
+        assert_eq!(result.global_vars.get("continuation").unwrap(), "later");
+    }
+
+    #[test]
+    fn intervals_settle_async_tests_and_assertions_follow_their_test() {
+        let script = r#"
+            client.test("resolves from an interval", async () => {
+                let polls = 0;
+                await new Promise(resolve => {
+                    const id = setInterval(() => {
+                        polls++;
+                        if (polls === 3) {
+                            clearInterval(id);
+                            resolve();
+                        }
+                    }, 1);
+                });
+                client.assert(polls === 3, "polled three times");
+            });
+            client.assert(response.status === 200, "top-level check");
+            (async () => {
+                await new Promise(resolve => {
+                    const id = setInterval(() => { clearInterval(id); resolve(); }, 1);
+                });
+                client.global.set("polled", "yes");
+            })();
+        "#;
+        let resp = dummy_response();
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        let messages: Vec<(&str, Vec<&str>)> = result
+            .test_results
+            .iter()
+            .map(|t| (t.name.as_str(), t.assertions.iter().map(|a| a.message.as_str()).collect()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                ("resolves from an interval", vec!["polled three times"]),
+                (IMPLICIT_TEST_NAME, vec!["top-level check"]),
+            ]
+        );
+        assert!(result.test_results.iter().all(|t| t.passed));
+        assert_eq!(result.global_vars.get("polled").unwrap(), "yes");
+    }
+
+    #[test]
+    fn unhandled_rejection_fails_the_handler() {
+        let script = r#"(async () => { await null; throw new Error("boom"); })();"#;
+        let resp = dummy_response();
+        let err = run(script, &resp, &HashMap::new()).unwrap_err();
+        assert!(matches!(&err, AppError::HandlerException { message: m, .. } if m.contains("boom")), "got {err:?}");
+
+        let script = r#"setTimeout(() => expect(response.status).to.equal(201), 1);"#;
+        let result = run(script, &resp, &HashMap::new()).unwrap();
+        assert_eq!(result.test_results.len(), 1);
+        assert!(!result.test_results[0].passed);
+    }
+
+    #[test]
+    fn timer_past_the_deadline_times_out() {
+        let limits = HandlerLimits {
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let script = "setTimeout(() => {}, 60000);";
+        let resp = dummy_response();
+        let err = HandlerRuntime::new(limits)
+            .execute(script, &ScriptOrigin::default(), &resp, &VariableStore::default())
+            .unwrap_err();
+        match err {
+            AppError::HandlerLimit { message, .. } => assert!(message.contains("timed out")),
+            other => panic!("expected HandlerLimit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn failed_assertions_record_their_http_line() {
+        let script = [
+            r#"client.test("assert", () => {"#,
+            r#"    client.assert(false, "nope");"#,
+            r#"});"#,
+            r#"client.test("expect", () => {"#,
+            r#"    expect(1).to.equal(2);"#,
+            r#"});"#,
+            r#"client.assert(false, "top level");"#,
+        ]
+        .join("\n");
+        let origin = ScriptOrigin {
+            line: 10,
+            ..ScriptOrigin::default()
+        };
+        let result = HandlerRuntime::new(HandlerLimits::default())
+            .execute(&script, &origin, &dummy_response(), &VariableStore::default())
+            .unwrap();
+        let lines: Vec<Option<usize>> = result.test_results.iter().map(TestResult::failure_line).collect();
+        assert_eq!(lines, [Some(11), Some(14), Some(16)], "{:?}", result.test_results);
+    }
+
+    #[test]
+    fn console_methods_log_with_levels() {
+        let script = r#"
+            console.log("total", response.body.totalElements, { page: 1 });
+            console.warn("slow");
+            console.error(new TypeError("bad"));
+            client.log([1, 2]);
+            console.table([{ id: 1, name: "a" }, { id: 2 }]);
+        "#;
+        let resp = dummy_response();
+        let logs = run(script, &resp, &HashMap::new()).unwrap().log_output;
+        let levels: Vec<LogLevel> = logs.iter().map(|l| l.level).collect();
+        assert_eq!(
+            levels,
+            [LogLevel::Log, LogLevel::Warn, LogLevel::Error, LogLevel::Log, LogLevel::Log]
+        );
+        assert_eq!(logs[0].message, "total 12 {\n  \"page\": 1\n}");
+        assert_eq!(logs[2].message, "TypeError: bad");
+        assert_eq!(logs[3].message, "[\n  1,\n  2\n]");
+        assert_eq!(
+            logs[4].message,
+            "┌─────────┬────┬──────┐\n\
+             │ (index) │ id │ name │\n\
+             ├─────────┼────┼──────┤\n\
+             │ 0       │ 1  │ a    │\n\
+             │ 1       │ 2  │      │\n\
+             └─────────┴────┴──────┘"
+        );
+    }
+
+    #[test]
+    fn client_fetch_sends_requests() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !String::from_utf8_lossy(&request).contains("\"code\"") {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let body = r#"{"otp":"123456"}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        // `{{variables}}` resolve against the environment and live globals
+        let script = r#"
+            client.global.set("mailbox", "alice");
+            const mail = client.fetch("post", "{{mail_host}}/messages", {
+                headers: { "X-Mailbox": "{{mailbox}}" },
+                body: { code: true }
+            });
+            client.global.set("otp", mail.body.otp);
+            client.global.set("type", mail.headers.valueOf("content-type"));
+        "#;
+        let variables = VariableStore::new(HashMap::from([(
+            "mail_host".to_string(),
+            format!("http://127.0.0.1:{port}"),
+        )]));
+        let resp = dummy_response();
+        let result = HandlerRuntime::new(HandlerLimits::default())
+            .execute(script, &ScriptOrigin::default(), &resp, &variables)
+            .unwrap();
+        assert_eq!(result.global_vars.get("otp").unwrap(), "123456");
+        assert_eq!(result.global_vars.get("type").unwrap(), "application/json");
+
+        let request = server.join().unwrap().to_lowercase();
+        assert!(request.starts_with("post /messages"), "{request}");
+        assert!(request.contains("x-mailbox: alice"), "{request}");
+        assert!(request.contains("content-type: application/json"), "{request}");
+
+        let err = run(r#"client.fetch("BREW", "http://127.0.0.1:1/")"#, &resp, &HashMap::new())
+            .unwrap_err();
+        assert!(matches!(&err, AppError::HandlerException { message: m, .. } if m.contains("unsupported method")), "{err:?}");
+    }
 }
@@ -0,0 +1,243 @@
+use std::sync::LazyLock;
+
+use boa_engine::{Context, JsError};
+use regex::Regex;
+
+use crate::js::expect::EXPECT_SOURCE_PATH;
+use crate::js::runtime::ScriptOrigin;
+
+/// Path given to handler sources so their frames can be told apart in boa backtraces.
+pub const HANDLER_SOURCE_PATH: &str = "<handler>";
+
+// boa syntax errors: "... at line 2, col 9"
+static SYNTAX_POSITION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r" at line (\d+), col (\d+)").unwrap());
+
+// boa backtrace frames: "    at name (path:line:col)", with "?:?" when the
+// position is unknown
+static FRAME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*at (.*) \((.*):(\d+|\?):(\d+|\?)\)$").unwrap());
+
+// Position suffix boa appends to thrown error objects: "Error: boom (path:3:9)"
+static THROWN_POSITION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r" \(([^()]*):(\d+):(\d+)\)$").unwrap());
+
+/// Maps positions in a handler script back to the `.http` file it came from.
+#[derive(Debug, Clone, Default)]
+pub struct HandlerSourceMap {
+    origin: ScriptOrigin,
+    lines: Vec<String>,
+}
+
+impl HandlerSourceMap {
+    pub fn new(script: &str, origin: &ScriptOrigin) -> Self {
+        Self {
+            origin: origin.clone(),
+            lines: script.lines().map(str::to_string).collect(),
+        }
+    }
+
+    /// Convert a 1-based line in the handler script to a line in the `.http` file.
+    pub fn http_line(&self, script_line: usize) -> usize {
+        if self.origin.line == 0 {
+            script_line
+        } else {
+            self.origin.line + script_line - 1
+        }
+    }
+
+    /// Render a JS error with its location in the `.http` file, a code excerpt
+    /// and the remapped backtrace.
+    pub fn describe_error(&self, error: &JsError) -> String {
+        let (message, position, frames) = self.locate(error);
+
+        let mut out = message;
+        if let Some((script_line, col)) = position {
+            out.push_str(&format!(
+                "\n  --> {} (request '{}')",
+                self.location(script_line, col),
+                self.origin.request
+            ));
+            out.push_str(&self.excerpt(script_line, col));
+        }
+        for frame in frames {
+            out.push('\n');
+            out.push_str(&frame);
+        }
+        out
+    }
+
+    /// The `.http` line a JS error was raised at, if it came from the handler.
+    pub fn error_line(&self, error: &JsError) -> Option<usize> {
+        let (_, position, _) = self.locate(error);
+        position.map(|(script_line, _)| self.http_line(script_line))
+    }
+
+    /// The `.http` line of the innermost handler frame on the call stack, for
+    /// attributing assertions made by native functions.
+    pub fn call_site_line(&self, context: &Context) -> Option<usize> {
+        context.stack_trace().find_map(|frame| {
+            let location = frame.position();
+            if location.path.to_string() != HANDLER_SOURCE_PATH {
+                return None;
+            }
+            let line = location.position?.line_number();
+            Some(self.http_line(line as usize))
+        })
+    }
+
+    /// Split an error into its message, its position in the handler script
+    /// (line, column) and its backtrace frames remapped to the `.http` file.
+    fn locate(&self, error: &JsError) -> (String, Option<(usize, usize)>, Vec<String>) {
+        let text = format!("{error}");
+        let mut lines = text.lines();
+        let mut message = lines.next().unwrap_or_default().to_string();
+        let mut position = None;
+
+        if let Some(caps) = SYNTAX_POSITION_RE.captures(&message) {
+            position = Some((parse_num(&caps[1]), parse_num(&caps[2])));
+            message = SYNTAX_POSITION_RE.replace(&message, "").into_owned();
+        } else if let Some(caps) = THROWN_POSITION_RE.captures(&message) {
+            if &caps[1] == HANDLER_SOURCE_PATH {
+                position = Some((parse_num(&caps[2]), parse_num(&caps[3])));
+            }
+            message = THROWN_POSITION_RE.replace(&message, "").into_owned();
+        }
+
+        let mut frames = Vec::new();
+        for line in lines {
+            let Some(caps) = FRAME_RE.captures(line) else {
+                frames.push(format!("    {}", line.trim()));
+                continue;
+            };
+            if &caps[2] == HANDLER_SOURCE_PATH {
+                let frame = match (caps[3].parse(), caps[4].parse()) {
+                    (Ok(script_line), Ok(col)) => {
+                        position.get_or_insert((script_line, col));
+                        self.location(script_line, col)
+                    }
+                    _ => self.unknown_location(),
+                };
+                frames.push(format!("    at {} ({frame})", &caps[1]));
+            } else if &caps[2] != EXPECT_SOURCE_PATH {
+                frames.push(format!("    {}", line.trim()));
+            }
+        }
+        (message, position, collapse_repeats(frames))
+    }
+
+    fn location(&self, script_line: usize, col: usize) -> String {
+        let line = self.http_line(script_line);
+        if self.origin.file.as_os_str().is_empty() {
+            format!("line {line}:{col}")
+        } else {
+            format!("{}:{line}:{col}", self.origin.file.display())
+        }
+    }
+
+    fn unknown_location(&self) -> String {
+        if self.origin.file.as_os_str().is_empty() {
+            format!("request '{}'", self.origin.request)
+        } else {
+            self.origin.file.display().to_string()
+        }
+    }
+
+    fn excerpt(&self, script_line: usize, col: usize) -> String {
+        let Some(code) = script_line
+            .checked_sub(1)
+            .and_then(|idx| self.lines.get(idx))
+        else {
+            return String::new();
+        };
+        let number = self.http_line(script_line).to_string();
+        let gutter = " ".repeat(number.len());
+        let caret_pad = " ".repeat(col.saturating_sub(1));
+        format!("\n{gutter} |\n{number} | {code}\n{gutter} | {caret_pad}^")
+    }
+}
+
+/// Fold runs of identical frames (runaway recursion) into the first frame and
+/// a count of the repeats.
+fn collapse_repeats(frames: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut repeats = 0;
+    for frame in frames {
+        if out.last() == Some(&frame) {
+            repeats += 1;
+            continue;
+        }
+        if repeats > 0 {
+            out.push(format!("    ... previous frame repeated {repeats} more times"));
+            repeats = 0;
+        }
+        out.push(frame);
+    }
+    if repeats > 0 {
+        out.push(format!("    ... previous frame repeated {repeats} more times"));
+    }
+    out
+}
+
+fn parse_num(s: &str) -> usize {
+    s.parse().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::Source;
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    fn eval_error(script: &str) -> JsError {
+        let mut context = Context::default();
+        context
+            .eval(Source::from_bytes(script).with_path(Path::new(HANDLER_SOURCE_PATH)))
+            .unwrap_err()
+    }
+
+    fn origin() -> ScriptOrigin {
+        ScriptOrigin {
+            file: PathBuf::from("api.http"),
+            request: "login".to_string(),
+            line: 10,
+        }
+    }
+
+    #[test]
+    fn maps_runtime_error_to_http_line() {
+        let script = "var a = 1;\nnull.foo;";
+        let map = HandlerSourceMap::new(script, &origin());
+        let described = map.describe_error(&eval_error(script));
+        assert!(described.starts_with("TypeError:"), "{described}");
+        assert!(described.contains("--> api.http:11:"), "{described}");
+        assert!(described.contains("11 | null.foo;"), "{described}");
+        assert!(described.contains("at <main> (api.http:11:"), "{described}");
+    }
+
+    #[test]
+    fn collapses_repeated_recursion_frames() {
+        let script = "var a = 1;\nfunction f() { return f(); }\nf();";
+        let mut context = Context::default();
+        context.runtime_limits_mut().set_recursion_limit(64);
+        let error = context
+            .eval(Source::from_bytes(script).with_path(Path::new(HANDLER_SOURCE_PATH)))
+            .unwrap_err();
+        let map = HandlerSourceMap::new(script, &origin());
+        let described = map.describe_error(&error);
+        assert!(described.contains("--> api.http:11:"), "{described}");
+        assert_eq!(described.matches("at f (api.http:11:").count(), 1, "{described}");
+        assert!(described.contains("previous frame repeated"), "{described}");
+    }
+
+    #[test]
+    fn maps_syntax_error_position() {
+        let script = "var a = 1;\nvar b = ;";
+        let map = HandlerSourceMap::new(script, &origin());
+        let described = map.describe_error(&eval_error(script));
+        assert!(!described.contains("at line 2"), "{described}");
+        assert!(described.contains("--> api.http:11:9"), "{described}");
+        assert!(described.contains("   |         ^"), "{described}");
+    }
+}
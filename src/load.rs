@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+
+use crate::error::AppError;
+use crate::report::RequestReport;
+
+/// Which iterations of a `--repeat` request run checks: `??` assertions,
+/// `# @schema`, `# @snapshot` and the response handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RepeatChecks {
+    /// Only the warm-up request, which is reported like a normal request
+    First,
+    /// Every iteration; those with failed tests or handler errors are counted
+    Every,
+    /// None, so only latency and status codes are measured
+    Skip,
+}
+
+/// One iteration of a `--repeat` request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    /// Status code, or `None` if the request couldn't be sent.
+    pub status: Option<u16>,
+    pub elapsed_ms: u128,
+    /// A test failed or the handler errored.
+    pub failed_checks: bool,
+}
+
+impl Sample {
+    pub fn of(record: &RequestReport) -> Self {
+        Self {
+            status: record.response.as_ref().map(|r| r.status),
+            elapsed_ms: record.elapsed_ms(),
+            failed_checks: record.tests.iter().any(|t| !t.passed)
+                || (record.response.is_some() && !record.errors.is_empty()),
+        }
+    }
+
+    /// The request couldn't be sent or got a 4xx or 5xx status.
+    pub fn is_error(&self) -> bool {
+        self.status.is_none_or(|status| status >= 400)
+    }
+}
+
+/// What `--repeat` measured for one request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadStats {
+    pub iterations: usize,
+    pub concurrency: usize,
+    /// Wall-clock time for all iterations. Besides the requests themselves it
+    /// covers building them and running their checks.
+    pub duration: Duration,
+    /// Iterations that couldn't be sent or got a 4xx or 5xx status.
+    pub errors: usize,
+    /// Iterations whose checks failed (when checks ran).
+    pub failed_checks: usize,
+    /// Latency percentiles of the responses received, in milliseconds.
+    pub p50_ms: u128,
+    pub p90_ms: u128,
+    pub p99_ms: u128,
+    pub max_ms: u128,
+    /// Responses per status code; requests that couldn't be sent aren't included.
+    pub statuses: BTreeMap<u16, usize>,
+}
+
+impl LoadStats {
+    pub fn new(samples: &[Sample], concurrency: usize, duration: Duration) -> Self {
+        let mut latencies: Vec<u128> = samples
+            .iter()
+            .filter(|s| s.status.is_some())
+            .map(|s| s.elapsed_ms)
+            .collect();
+        latencies.sort_unstable();
+        let mut statuses = BTreeMap::new();
+        for status in samples.iter().filter_map(|s| s.status) {
+            *statuses.entry(status).or_default() += 1;
+        }
+        Self {
+            iterations: samples.len(),
+            concurrency,
+            duration,
+            errors: samples.iter().filter(|s| s.is_error()).count(),
+            failed_checks: samples.iter().filter(|s| s.failed_checks).count(),
+            p50_ms: percentile(&latencies, 50),
+            p90_ms: percentile(&latencies, 90),
+            p99_ms: percentile(&latencies, 99),
+            max_ms: latencies.last().copied().unwrap_or(0),
+            statuses,
+        }
+    }
+
+    /// Iterations per second.
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.duration.as_secs_f64();
+        if seconds > 0.0 { self.iterations as f64 / seconds } else { 0.0 }
+    }
+
+    /// Share of iterations that were errors, from 0 to 1.
+    pub fn error_rate(&self) -> f64 {
+        if self.iterations == 0 { 0.0 } else { self.errors as f64 / self.iterations as f64 }
+    }
+}
+
+/// Nearest-rank percentile of sorted values.
+fn percentile(sorted: &[u128], p: usize) -> u128 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Run `iterations` iterations on up to `concurrency` threads, each with its
+/// own state from `init`, stopping at the first error that prevents a request
+/// from being built. Every state is built before the clock starts, so the
+/// returned duration covers only the iterations. Returns the samples, in no
+/// particular order, and how long they took.
+pub fn run<W: Send>(
+    iterations: usize,
+    concurrency: usize,
+    mut init: impl FnMut() -> Result<W, AppError>,
+    iteration: impl Fn(&mut W) -> Result<Sample, AppError> + Sync,
+) -> Result<(Vec<Sample>, Duration), AppError> {
+    let states = (0..concurrency.clamp(1, iterations.max(1)))
+        .map(|_| init())
+        .collect::<Result<Vec<_>, _>>()?;
+    let started = Instant::now();
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let per_worker = thread::scope(|scope| {
+        let workers: Vec<_> = states
+            .into_iter()
+            .map(|mut state| {
+                let (next, stop, iteration) = (&next, &stop, &iteration);
+                scope.spawn(move || {
+                    let mut samples = Vec::new();
+                    while !stop.load(Ordering::Relaxed)
+                        && next.fetch_add(1, Ordering::Relaxed) < iterations
+                    {
+                        match iteration(&mut state) {
+                            Ok(sample) => samples.push(sample),
+                            Err(e) => {
+                                stop.store(true, Ordering::Relaxed);
+                                return Err(e);
+                            }
+                        }
+                    }
+                    Ok(samples)
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("load worker panicked"))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    Ok((per_worker.into_iter().flatten().collect(), started.elapsed()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_percentiles_error_rate_and_status_histogram() {
+        let sample = |status, elapsed_ms| Sample {
+            status,
+            elapsed_ms,
+            failed_checks: false,
+        };
+        let mut samples: Vec<Sample> = (1..=96).map(|ms| sample(Some(200), ms)).collect();
+        samples.push(sample(Some(404), 200));
+        samples.push(sample(Some(503), 300));
+        samples.push(sample(Some(503), 400));
+        samples.push(Sample {
+            failed_checks: true,
+            ..sample(None, 0)
+        });
+
+        let stats = LoadStats::new(&samples, 20, Duration::from_secs(4));
+        assert_eq!(stats.iterations, 100);
+        assert_eq!((stats.p50_ms, stats.p90_ms, stats.p99_ms, stats.max_ms), (50, 90, 400, 400));
+        assert_eq!(stats.statuses, BTreeMap::from([(200, 96), (404, 1), (503, 2)]));
+        assert_eq!(stats.errors, 4);
+        assert_eq!(stats.failed_checks, 1);
+        assert_eq!(stats.throughput(), 25.0);
+        assert_eq!(stats.error_rate(), 0.04);
+
+        let empty = LoadStats::new(&[], 1, Duration::ZERO);
+        assert_eq!((empty.p50_ms, empty.max_ms, empty.throughput(), empty.error_rate()), (0, 0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn runs_each_iteration_once_and_stops_at_the_first_error() {
+        let sample = Sample {
+            status: Some(200),
+            elapsed_ms: 1,
+            failed_checks: false,
+        };
+        let workers = AtomicUsize::new(0);
+        let (samples, _) = run(
+            50,
+            4,
+            || Ok(workers.fetch_add(1, Ordering::Relaxed)),
+            |_| Ok(sample),
+        )
+        .unwrap();
+        assert_eq!(samples.len(), 50);
+        assert_eq!(workers.into_inner(), 4);
+
+        let sent = AtomicUsize::new(0);
+        let result = run(50, 4, || Ok(()), |_| {
+            match sent.fetch_add(1, Ordering::Relaxed) {
+                10 => Err(AppError::JavaScript("broken".into())),
+                _ => Ok(sample),
+            }
+        });
+        assert!(matches!(result, Err(AppError::JavaScript(_))));
+        assert!(sent.into_inner() < 50);
+
+        // A worker that can't be set up stops the run before any iteration
+        let sent = AtomicUsize::new(0);
+        let result = run(
+            50,
+            4,
+            || Err(AppError::JavaScript("no context".into())),
+            |_: &mut ()| {
+                sent.fetch_add(1, Ordering::Relaxed);
+                Ok(sample)
+            },
+        );
+        assert!(matches!(result, Err(AppError::JavaScript(_))));
+        assert_eq!(sent.into_inner(), 0);
+    }
+}
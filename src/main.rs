@@ -1,15 +1,27 @@
+mod assertion;
+mod diff;
 mod env;
 mod error;
+mod files;
 mod http;
 mod js;
+mod load;
 mod output;
+mod parallel;
 mod parser;
+mod report;
+mod schema;
+mod select;
+mod snapshot;
 mod variable;
 
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::Duration;
 
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 
 use crate::error::AppError;
 use crate::variable::VariableStore;
@@ -17,8 +29,9 @@ use crate::variable::VariableStore;
 #[derive(Parser, Debug)]
 #[command(name = "httprun", about = "Run IntelliJ .http request files from the terminal")]
 struct Cli {
-    /// Path to the .http file
-    file: PathBuf,
+    /// .http files, directories (searched recursively) or glob patterns
+    #[arg(required = true, value_name = "FILE")]
+    files: Vec<String>,
 
     /// Environment name to use (from http-client.env.json)
     #[arg(long)]
@@ -28,13 +41,23 @@ struct Cli {
     #[arg(long, default_value = "http-client.env.json")]
     env_file: PathBuf,
 
-    /// Run a single request by name
-    #[arg(long)]
-    name: Option<String>,
+    /// Run requests whose name matches a regex (case-insensitive); repeatable
+    #[arg(long, value_name = "PATTERN")]
+    name: Vec<String>,
+
+    /// Run requests by 1-based index or range (3, 2-5, 4-); repeatable or comma-separated
+    #[arg(long, value_name = "N|N-M", value_delimiter = ',')]
+    index: Vec<select::IndexRange>,
+
+    /// Run requests by `# @tag`: any listed tag, none of the !-prefixed ones
+    /// (e.g. smoke,!slow); repeat to require every expression
+    #[arg(long, value_name = "TAGS")]
+    tags: Vec<select::TagFilter>,
 
-    /// Run a single request by 1-based index
+    /// Also run the requests whose handlers set globals that the selected
+    /// requests use (in addition to `# @depends`)
     #[arg(long)]
-    index: Option<usize>,
+    infer_deps: bool,
 
     /// Show full request/response details
     #[arg(short, long)]
@@ -43,23 +66,222 @@ struct Cli {
     /// Parse and display without executing
     #[arg(long)]
     dry_run: bool,
+
+    /// Maximum number of client.jump() calls per run (guards against loops)
+    #[arg(long, default_value_t = 100)]
+    max_jumps: usize,
+
+    /// Wall-clock timeout for each response handler, in milliseconds
+    #[arg(long, default_value_t = 10_000)]
+    handler_timeout: u64,
+
+    /// Maximum iterations of any single loop in a response handler
+    #[arg(long, default_value_t = 10_000_000)]
+    handler_loop_limit: u64,
+
+    /// Maximum function call depth in a response handler
+    #[arg(long, default_value_t = 512)]
+    handler_recursion_limit: usize,
+
+    /// Overwrite stored response snapshots instead of comparing against them
+    #[arg(long)]
+    update_snapshots: bool,
+
+    /// Write a report as <format>[=<path>] (formats: junit, html, tap, teamcity;
+    /// stdout without a path or with -); repeatable
+    #[arg(long, value_name = "FORMAT[=PATH]")]
+    report: Vec<report::ReportSpec>,
+
+    /// Format of the results printed to stdout
+    #[arg(long, value_enum, default_value_t = output::OutputFormat::Text)]
+    output: output::OutputFormat,
+
+    /// Run up to N requests at once in files marked `# @parallel`, each after
+    /// the requests it depends on
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    parallel: usize,
+
+    /// Send each selected request N more times after a warm-up request and
+    /// report latency percentiles, throughput, error rate and status codes of those N
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["parallel", "dry_run"],
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    repeat: Option<usize>,
+
+    /// How many --repeat iterations to send at once
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        requires = "repeat",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    concurrency: usize,
+
+    /// Which --repeat iterations run assertions and response handlers
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = load::RepeatChecks::First, requires = "repeat")]
+    repeat_checks: load::RepeatChecks,
+
+    /// Keep client.global variables set in one file for the files after it
+    #[arg(long)]
+    carry_globals: bool,
+}
+
+/// What running one file contributed to the run.
+enum FileOutcome {
+    /// The file has no requests.
+    Empty,
+    /// None of its requests matched --name/--index/--tags.
+    NoMatch,
+    Ran,
+    /// A handler called client.exit(); no further requests or files run.
+    Exited,
 }
 
 fn main() {
     let cli = Cli::parse();
+    let console = match console_output(&cli) {
+        Ok(console) => console,
+        Err(message) => Cli::command().error(ErrorKind::ArgumentConflict, message).exit(),
+    };
+    let mut reporter = output::reporter(console, cli.verbose);
+
+    let success = match run(cli, reporter.as_mut()) {
+        Ok(success) => success,
+        Err(e) => {
+            reporter.error(&format!("{}", e));
+            false
+        }
+    };
+    reporter.finish();
 
-    if let Err(e) = run(cli) {
-        output::print_error(&format!("{}", e));
+    // Exit with failure if any tests failed or errors occurred
+    if !success {
         process::exit(1);
     }
 }
 
-fn run(cli: Cli) -> Result<(), AppError> {
+/// The `--output` format printed to stdout, or `None` when a `--report` is
+/// written there instead. Only one of them can have stdout.
+fn console_output(cli: &Cli) -> Result<Option<output::OutputFormat>, String> {
+    // Dry runs don't write reports
+    if cli.dry_run {
+        return Ok(Some(cli.output));
+    }
+    match cli.report.iter().filter(|r| r.to_stdout()).count() {
+        0 => Ok(Some(cli.output)),
+        1 if cli.output == output::OutputFormat::Text => Ok(None),
+        1 => Err("a --report written to stdout can't be combined with --output json or jsonl; \
+                  give the report a path"
+            .to_string()),
+        _ => Err("only one --report can be written to stdout; give the others a path".to_string()),
+    }
+}
+
+/// Run the requests in every file given on the command line, in order,
+/// returning whether every test passed without errors.
+fn run(cli: Cli, reporter: &mut dyn output::Reporter) -> Result<bool, AppError> {
+    let files = files::expand(&cli.files)?;
+    let mut context = RunContext {
+        cli: &cli,
+        selection: select::Selection::new(&cli.name, &cli.index, &cli.tags)?,
+        worker: RequestWorker::new(http::Client::new(), &handler_limits(&cli), cli.update_snapshots),
+    };
+    reporter.run_started(&files);
+    let mut summary = output::Summary::default();
+    let mut run_report = report::RunReport::default();
+    let mut globals = HashMap::new();
+    let (mut any_requests, mut any_matched) = (false, false);
+
+    for file in &files {
+        let mut suite = report::SuiteReport::new(file);
+        let outcome = run_file(
+            &mut context,
+            file,
+            reporter,
+            &mut suite,
+            &mut summary,
+            &mut globals,
+        );
+        if !suite.requests.is_empty() {
+            summary.files += 1;
+        }
+        if let Err(e) = &outcome {
+            suite.requests.push(report::RequestReport::file_error(file, e));
+        }
+        if !suite.requests.is_empty() {
+            run_report.suites.push(suite);
+        }
+        if !cli.carry_globals {
+            globals.clear();
+        }
+        match outcome {
+            Ok(FileOutcome::Empty) => {}
+            Ok(FileOutcome::NoMatch) => any_requests = true,
+            Ok(FileOutcome::Ran) => (any_requests, any_matched) = (true, true),
+            Ok(FileOutcome::Exited) => {
+                any_matched = true;
+                break;
+            }
+            // A single file fails as a whole; with several, the others still run
+            Err(e) if files.len() == 1 => {
+                if !cli.dry_run {
+                    report::write_reports(&cli.report, &run_report)?;
+                }
+                return Err(e);
+            }
+            Err(e) => {
+                reporter.error(&format!("{}: {}", file.display(), e));
+                summary.errors += 1;
+                any_matched = true;
+            }
+        }
+    }
+
+    if !any_matched {
+        reporter.error(if any_requests {
+            "No matching requests found"
+        } else if files.len() == 1 {
+            "No requests found in file"
+        } else {
+            "No requests found in any file"
+        });
+        return Ok(true);
+    }
+    if cli.dry_run {
+        return Ok(true);
+    }
+
+    report::write_reports(&cli.report, &run_report)?;
+    reporter.summary(&summary);
+    Ok(summary.success())
+}
+
+/// Run the selected requests in one `.http` file, adding them to `suite` and
+/// their results to `summary`. `globals` holds the `client.global` variables
+/// the file starts with and receives those it ends with.
+fn run_file(
+    run: &mut RunContext,
+    file: &Path,
+    reporter: &mut dyn output::Reporter,
+    suite: &mut report::SuiteReport,
+    summary: &mut output::Summary,
+    globals: &mut HashMap<String, String>,
+) -> Result<FileOutcome, AppError> {
+    let cli = run.cli;
     // Read and parse the .http file
-    let content = std::fs::read_to_string(&cli.file).map_err(|e| {
+    let content = std::fs::read_to_string(file).map_err(|e| {
         AppError::Io(std::io::Error::new(
             e.kind(),
-            format!("{}: {}", cli.file.display(), e),
+            format!("{}: {}", file.display(), e),
         ))
     })?;
 
@@ -67,15 +289,14 @@ fn run(cli: Cli) -> Result<(), AppError> {
     let all_requests = parse_result.requests;
 
     if all_requests.is_empty() {
-        output::print_error("No requests found in file");
-        return Ok(());
+        return Ok(FileOutcome::Empty);
     }
 
     // Load environment variables
     let env_vars = if let Some(env_name) = &cli.env {
         // Resolve env file relative to the .http file's directory
         let env_file = if cli.env_file.is_relative() {
-            if let Some(parent) = cli.file.parent() {
+            if let Some(parent) = file.parent() {
                 parent.join(&cli.env_file)
             } else {
                 cli.env_file.clone()
@@ -90,227 +311,721 @@ fn run(cli: Cli) -> Result<(), AppError> {
     };
 
     let mut var_store = VariableStore::new(env_vars);
+    var_store.merge_globals(globals, &HashSet::new());
 
     // Load in-place variables
     for (name, value) in &parse_result.in_place_vars {
         var_store.set_in_place(name.clone(), value.clone());
     }
 
-    // Filter requests if --name or --index specified
-    let requests: Vec<(usize, &parser::ParsedRequest)> = if let Some(name) = &cli.name {
-        all_requests
-            .iter()
-            .enumerate()
-            .filter(|(_, r)| {
-                r.name
-                    .as_ref()
-                    .is_some_and(|n| n.to_lowercase().contains(&name.to_lowercase()))
-            })
-            .collect()
-    } else if let Some(index) = cli.index {
-        if index == 0 || index > all_requests.len() {
-            return Err(AppError::Parse {
-                line: 0,
-                message: format!(
-                    "Index {} out of range (1-{})",
-                    index,
-                    all_requests.len()
-                ),
-            });
-        }
-        vec![(index - 1, &all_requests[index - 1])]
-    } else {
-        all_requests.iter().enumerate().collect()
-    };
+    run.selection.check_indexes(all_requests.len())?;
+    let selected = run.selection.select(&all_requests);
+    if selected.is_empty() {
+        return Ok(FileOutcome::NoMatch);
+    }
 
-    if requests.is_empty() {
-        output::print_error("No matching requests found");
-        return Ok(());
+    // Prerequisites of the selected requests run before them
+    let plan = select::with_dependencies(
+        &all_requests,
+        &selected,
+        cli.infer_deps.then_some(&var_store),
+    )?;
+    let requests = plan.requests;
+    reporter.file_started(file);
+    for warning in &plan.warnings {
+        reporter.notice(warning);
+    }
+    let prerequisites: Vec<String> = requests
+        .iter()
+        .filter(|(i, _)| !selected.iter().any(|(s, _)| s == i))
+        .map(|(i, r)| r.name.clone().unwrap_or_else(|| format!("#{}", i + 1)))
+        .collect();
+    if !prerequisites.is_empty() {
+        reporter.notice(&format!("Running prerequisites: {}", prerequisites.join(", ")));
     }
 
     // Dry run mode
     if cli.dry_run {
-        println!(
-            "Dry run: {} request(s) from {}",
-            requests.len(),
-            cli.file.display()
-        );
+        reporter.dry_run_started(requests.len(), file);
         for (i, req) in &requests {
             let mut resolved = (*req).clone();
             // Try to substitute variables (best-effort for dry run)
             if let Ok(url) = var_store.substitute(&resolved.url) {
-                resolved.url = ensure_http_scheme(&url);
+                resolved.url = http::ensure_http_scheme(&url);
             }
-            output::print_dry_run_request(i + 1, &resolved);
+            reporter.dry_run_request(i + 1, &resolved);
         }
-        return Ok(());
+        return Ok(FileOutcome::Ran);
     }
 
-    // Execute requests
-    let mut passed_tests = 0usize;
-    let mut failed_tests = 0usize;
-    let mut error_count = 0usize;
+    let context = RequestContext {
+        file,
+        // `# @schema` paths are resolved against the .http file's directory
+        base_dir: file.parent().unwrap_or(Path::new("")).to_path_buf(),
+        snapshots: snapshot::SnapshotSettings::new(file, cli.update_snapshots),
+        limits: handler_limits(cli),
+    };
 
-    for (i, req) in &requests {
-        // Clone and resolve variables
-        let mut resolved = (*req).clone();
-        let resolved_url = var_store.substitute(&resolved.url)?;
-        resolved.url = ensure_http_scheme(&resolved_url);
+    if cli.parallel > 1 && parse_result.parallel {
+        let dependencies = plan.dependencies;
+        let mut schedule = ParallelFile::new(&dependencies, &var_store, reporter);
+        parallel::run(
+            &dependencies,
+            cli.parallel,
+            &mut schedule,
+            || context.worker(),
+            |worker, (position, vars): (usize, VariableStore)| {
+                let (i, req) = requests[position];
+                let mut recorder = output::Recorder::default();
+                let run = run_request(&context, i, req, &vars, true, worker, &mut recorder);
+                let run = run.map(|mut run| {
+                    // Keep only what the handler changed, for merging in file order
+                    run.globals = run
+                        .globals
+                        .map(|handler_globals| changed_globals(vars.globals(), handler_globals));
+                    run
+                });
+                (run, recorder)
+            },
+        );
+        return schedule.finish_file(suite, summary, globals);
+    }
 
-        // Substitute variables in headers
-        for header in &mut resolved.headers {
-            header.value = var_store.substitute(&header.value)?;
+    let worker = &mut run.worker;
+
+    // Flow control requested by handlers (client.skip / client.jump / client.exit)
+    let mut pending_skips: Vec<String> = Vec::new();
+    let mut jumps = 0usize;
+    let mut position = 0usize;
+    let mut outcome = FileOutcome::Ran;
+
+    while position < requests.len() {
+        let (i, req) = requests[position];
+        position += 1;
+
+        if let Some(name) = &req.name
+            && let Some(idx) = pending_skips.iter().position(|s| s.eq_ignore_ascii_case(name))
+        {
+            pending_skips.remove(idx);
+            reporter.request_skipped(i + 1, req);
+            let record = report::RequestReport::skipped(i + 1, req);
+            reporter.request_finished(&record);
+            suite.requests.push(record);
+            continue;
         }
 
-        // Substitute variables in body
-        if let Some(body) = &resolved.body {
-            resolved.body = Some(var_store.substitute(body)?);
+        let run = match cli.repeat {
+            Some(repeat) if selected.iter().any(|(s, _)| *s == i) => {
+                let options = LoadOptions {
+                    repeat,
+                    concurrency: cli.concurrency,
+                    checks: cli.repeat_checks,
+                };
+                run_load(
+                    &context,
+                    i,
+                    req,
+                    &var_store,
+                    &options,
+                    worker,
+                    reporter,
+                )?
+            }
+            _ => run_request(&context, i, req, &var_store, true, worker, reporter)?,
+        };
+        let mut record = run.record;
+        if let Some((handler_globals, removed)) = &run.globals {
+            var_store.merge_globals(handler_globals, removed);
         }
 
-        output::print_request_header(i + 1, &resolved);
+        // Apply flow control requested by the handler
+        let mut stop = false;
+        if let Some(flow) = run.flow {
+            for name in flow.skip {
+                if !pending_skips.iter().any(|s| s.eq_ignore_ascii_case(&name)) {
+                    pending_skips.push(name);
+                }
+            }
 
-        if cli.verbose {
-            output::print_verbose_request(&resolved);
+            if flow.exit {
+                reporter.notice("Run stopped by client.exit()");
+                outcome = FileOutcome::Exited;
+                stop = true;
+            } else if let Some(target) = flow.jump_to {
+                let target_pos = requests.iter().position(|(_, r)| {
+                    r.name
+                        .as_ref()
+                        .is_some_and(|n| n.eq_ignore_ascii_case(&target))
+                });
+                jumps += 1;
+                let error = match target_pos {
+                    None => Some(format!("client.jump: no request named '{}'", target)),
+                    Some(_) if jumps > cli.max_jumps => Some(format!(
+                        "client.jump: exceeded --max-jumps ({}), stopping run",
+                        cli.max_jumps
+                    )),
+                    Some(target_pos) => {
+                        position = target_pos;
+                        None
+                    }
+                };
+                if let Some(message) = error {
+                    reporter.request_error(&message);
+                    record.errors.push(message.into());
+                    stop = true;
+                }
+            }
         }
 
-        // Execute HTTP request
-        match http::execute_request(&resolved) {
-            Ok(response) => {
-                output::print_response_status(&response);
+        summary.add(&record);
+        reporter.request_finished(&record);
+        suite.requests.push(record);
+        if stop {
+            break;
+        }
+    }
 
-                if cli.verbose {
-                    output::print_verbose_response(&response);
-                }
+    *globals = var_store.globals().clone();
+    Ok(outcome)
+}
+
+/// What every file in a run shares.
+struct RunContext<'a> {
+    cli: &'a Cli,
+    selection: select::Selection,
+    /// Sends the requests of files run one request at a time. Its handler
+    /// runtime lives for the whole run, so modules are loaded once.
+    worker: RequestWorker,
+}
 
-                // Run response handler if present
-                if let Some(handler) = &resolved.response_handler {
-                    match js::execute_handler(handler, &response, var_store.globals()) {
-                        Ok(result) => {
-                            // Merge global variables
-                            var_store.merge_globals(&result.global_vars);
-
-                            // Print logs
-                            if !result.log_output.is_empty() {
-                                output::print_log_output(&result.log_output);
-                            }
-
-                            // Print test results
-                            if !result.test_results.is_empty() {
-                                output::print_test_results(&result.test_results);
-                                for tr in &result.test_results {
-                                    if tr.passed {
-                                        passed_tests += 1;
-                                    } else {
-                                        failed_tests += 1;
-                                    }
-                                }
-                            }
+fn handler_limits(cli: &Cli) -> js::HandlerLimits {
+    js::HandlerLimits {
+        loop_iteration_limit: cli.handler_loop_limit,
+        recursion_limit: cli.handler_recursion_limit,
+        timeout: Duration::from_millis(cli.handler_timeout),
+    }
+}
+
+/// What every request in a file shares.
+struct RequestContext<'a> {
+    file: &'a Path,
+    base_dir: PathBuf,
+    snapshots: snapshot::SnapshotSettings,
+    limits: js::HandlerLimits,
+}
+
+impl RequestContext<'_> {
+    fn worker(&self) -> RequestWorker {
+        self.worker_with(http::Client::new())
+    }
+
+    /// A worker sending with `client`, sharing its connection pool.
+    fn worker_with(&self, client: http::Client) -> RequestWorker {
+        RequestWorker::new(client, &self.limits, self.snapshots.update)
+    }
+}
+
+/// What one thread sends requests with. Each `--parallel` or `--concurrency`
+/// worker has its own, so connections are reused within a worker.
+struct RequestWorker {
+    client: http::Client,
+    handler_runtime: js::HandlerRuntime,
+}
+
+impl RequestWorker {
+    fn new(client: http::Client, limits: &js::HandlerLimits, update_snapshots: bool) -> Self {
+        Self {
+            handler_runtime: js::HandlerRuntime::new(limits.clone())
+                .with_snapshot_updates(update_snapshots)
+                .with_client(client.clone()),
+            client,
+        }
+    }
+}
+
+/// What running one request produced.
+struct RequestRun {
+    record: report::RequestReport,
+    /// Flow control requested by the response handler.
+    flow: Option<js::FlowControl>,
+    /// The globals the response handler left, and those it removed.
+    globals: Option<GlobalChanges>,
+}
+
+type GlobalChanges = (HashMap<String, String>, HashSet<String>);
+
+/// Resolve, send and (with `checks`) check one request, reporting as it goes.
+/// Applying its globals and flow control, and reporting it finished, is up to
+/// the caller.
+fn run_request(
+    context: &RequestContext,
+    i: usize,
+    req: &parser::ParsedRequest,
+    var_store: &VariableStore,
+    checks: bool,
+    worker: &mut RequestWorker,
+    reporter: &mut dyn output::Reporter,
+) -> Result<RequestRun, AppError> {
+    let mut flow = None;
+    let mut globals = None;
+
+    // Clone and resolve variables
+    let mut resolved = req.clone();
+    let resolved_url = var_store.substitute(&resolved.url)?;
+    resolved.url = http::ensure_http_scheme(&resolved_url);
+
+    // Substitute variables in headers
+    for header in &mut resolved.headers {
+        header.value = var_store.substitute(&header.value)?;
+    }
+
+    // Substitute variables in body
+    if let Some(body) = &resolved.body {
+        resolved.body = Some(var_store.substitute(body)?);
+    }
+
+    reporter.request_started(i + 1, &resolved);
+    let mut record = report::RequestReport::new(i + 1, &resolved);
+
+    // Execute HTTP request
+    match http::execute_request(&worker.client, &resolved) {
+        Ok(response) => {
+            reporter.response_received(&response);
+            record.response = Some(response.clone());
+            if !checks {
+                return Ok(RequestRun {
+                    record,
+                    flow,
+                    globals,
+                });
+            }
+
+            // Check `??` assertions, then validate against `# @schema` files
+            let mut directive_results: Vec<js::TestResult> = resolved
+                .assertions
+                .iter()
+                .map(|a| assertion::evaluate(a, &response))
+                .collect();
+            for schema_ref in &resolved.schemas {
+                let schema_ref = var_store.substitute(schema_ref)?;
+                let path = context.base_dir.join(&schema_ref);
+                let outcome = match &response.body_json {
+                    Some(body) => schema::validate_file(body, &path),
+                    None => Err(AppError::Schema("response body is not JSON".to_string())),
+                };
+                directive_results.extend(schema::to_test_results(&schema_ref, outcome));
+            }
+
+            // Compare the body with its `# @snapshot`
+            if let Some(directive) = &resolved.snapshot {
+                let name = directive
+                    .name
+                    .clone()
+                    .or_else(|| resolved.name.clone())
+                    .unwrap_or_else(|| format!("request-{}", i + 1));
+                directive_results.push(snapshot::check(
+                    &context.snapshots,
+                    &name,
+                    &snapshot::body_value(&response),
+                    &directive.ignore,
+                ));
+            }
+
+            if !directive_results.is_empty() {
+                reporter.test_results(&directive_results);
+            }
+            record.tests.extend(directive_results);
+
+            // Run response handler if present
+            if let Some(handler) = &resolved.response_handler {
+                let origin = js::ScriptOrigin {
+                    file: context.file.to_path_buf(),
+                    request: resolved
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("#{}", i + 1)),
+                    line: resolved.handler_line_number,
+                };
+                match worker.handler_runtime.execute(handler, &origin, &response, var_store) {
+                    Ok(result) => {
+                        // Report logs
+                        if !result.log_output.is_empty() {
+                            reporter.logs(&result.log_output);
                         }
-                        Err(e) => {
-                            output::print_error(&format!("Handler error: {}", e));
-                            error_count += 1;
+
+                        // Report test results
+                        if !result.test_results.is_empty() {
+                            reporter.test_results(&result.test_results);
                         }
+
+                        record.tests.extend(result.test_results);
+                        record.logs.extend(result.log_output);
+                        globals = Some((result.global_vars, result.removed_globals));
+                        flow = Some(result.flow);
+                    }
+                    Err(e) => {
+                        let message = format!("Handler error: {}", e);
+                        reporter.request_error(&message);
+                        record.errors.push(report::RequestError {
+                            message,
+                            line: e.handler_line(),
+                        });
                     }
                 }
             }
-            Err(e) => {
-                output::print_error(&format!("{}", e));
-                error_count += 1;
-            }
+        }
+        Err(e) => {
+            let message = format!("{}", e);
+            reporter.request_error(&message);
+            record.errors.push(message.into());
         }
     }
 
-    // Print summary
-    output::print_summary(requests.len(), passed_tests, failed_tests, error_count);
+    Ok(RequestRun {
+        record,
+        flow,
+        globals,
+    })
+}
 
-    // Exit with failure if any tests failed or errors occurred
-    if failed_tests > 0 || error_count > 0 {
-        process::exit(1);
+/// How to send a request under `--repeat`.
+struct LoadOptions {
+    repeat: usize,
+    concurrency: usize,
+    checks: load::RepeatChecks,
+}
+
+/// Send a request once to warm up, then `options.repeat` more times, and record
+/// the latency and status statistics of those in its record. The warm-up is
+/// reported like a normal request (with checks unless they're skipped) and its
+/// globals are kept; the measured iterations are sent `options.concurrency` at
+/// a time, quietly. Flow control from handlers is ignored.
+fn run_load(
+    context: &RequestContext,
+    i: usize,
+    req: &parser::ParsedRequest,
+    var_store: &VariableStore,
+    options: &LoadOptions,
+    worker: &mut RequestWorker,
+    reporter: &mut dyn output::Reporter,
+) -> Result<RequestRun, AppError> {
+    let checks = options.checks != load::RepeatChecks::Skip;
+    let mut run = run_request(context, i, req, var_store, checks, worker, reporter)?;
+    run.flow = None;
+
+    let every = options.checks == load::RepeatChecks::Every;
+    let (samples, duration) = load::run(
+        options.repeat,
+        options.concurrency,
+        || {
+            // Share the warm-up request's connections, and start each JS
+            // thread before timing starts
+            let mut load_worker = context.worker_with(worker.client.clone());
+            if every {
+                load_worker.handler_runtime.warm_up()?;
+            }
+            Ok(load_worker)
+        },
+        |worker| {
+            let mut quiet = output::Recorder::default();
+            run_request(context, i, req, var_store, every, worker, &mut quiet)
+                .map(|run| load::Sample::of(&run.record))
+        },
+    )?;
+    let stats = load::LoadStats::new(&samples, options.concurrency, duration);
+    if every && stats.failed_checks > 0 {
+        let message = format!(
+            "{} of {} iterations failed their checks",
+            stats.failed_checks, stats.iterations
+        );
+        reporter.request_error(&message);
+        run.record.errors.push(message.into());
     }
+    run.record.load = Some(stats);
+    Ok(run)
+}
 
-    Ok(())
+/// The globals a handler changed, given those it started with: the ones it
+/// set to a new value and the ones it removed.
+fn changed_globals(
+    before: &HashMap<String, String>,
+    (after, removed): GlobalChanges,
+) -> GlobalChanges {
+    let set = after
+        .into_iter()
+        .filter(|(name, value)| before.get(name) != Some(value))
+        .collect();
+    (set, removed)
 }
 
-fn ensure_http_scheme(url: &str) -> String {
-    let trimmed = url.trim();
-    if has_url_scheme(trimmed) {
-        trimmed.to_string()
-    } else {
-        format!("https://{}", trimmed)
+/// A `# @parallel` file run with `--parallel`. Each request starts from the
+/// file's globals plus the changes made by the requests it depends on
+/// (directly or not), and the file ends with every request's changes applied
+/// in file order, so the outcome doesn't depend on which finished first.
+/// Each request's output is replayed in one piece when it finishes.
+struct ParallelFile<'a> {
+    var_store: &'a VariableStore,
+    reporter: &'a mut dyn output::Reporter,
+    /// Positions of each request's dependencies and theirs, in order.
+    ancestors: Vec<Vec<usize>>,
+    records: Vec<Option<report::RequestReport>>,
+    changes: Vec<Option<GlobalChanges>>,
+    outcome: FileOutcome,
+    error: Option<AppError>,
+}
+
+impl<'a> ParallelFile<'a> {
+    fn new(
+        dependencies: &[Vec<usize>],
+        var_store: &'a VariableStore,
+        reporter: &'a mut dyn output::Reporter,
+    ) -> Self {
+        let mut ancestors: Vec<Vec<usize>> = Vec::with_capacity(dependencies.len());
+        for depends_on in dependencies {
+            let mut all = std::collections::BTreeSet::new();
+            for &dependency in depends_on {
+                all.insert(dependency);
+                all.extend(ancestors[dependency].iter().copied());
+            }
+            ancestors.push(all.into_iter().collect());
+        }
+        Self {
+            var_store,
+            reporter,
+            ancestors,
+            records: vec![None; dependencies.len()],
+            changes: vec![None; dependencies.len()],
+            outcome: FileOutcome::Ran,
+            error: None,
+        }
+    }
+
+    /// Add the records to `suite` in file order and leave the file's final
+    /// globals in `globals`.
+    fn finish_file(
+        self,
+        suite: &mut report::SuiteReport,
+        summary: &mut output::Summary,
+        globals: &mut HashMap<String, String>,
+    ) -> Result<FileOutcome, AppError> {
+        for record in self.records.into_iter().flatten() {
+            summary.add(&record);
+            suite.requests.push(record);
+        }
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let mut var_store = self.var_store.clone();
+        for (set, removed) in self.changes.iter().flatten() {
+            var_store.merge_globals(set, removed);
+        }
+        *globals = var_store.globals().clone();
+        Ok(self.outcome)
     }
 }
 
-fn has_url_scheme(url: &str) -> bool {
-    let Some(idx) = url.find("://") else {
-        return false;
-    };
-    if idx == 0 {
-        return false;
+impl parallel::Schedule for ParallelFile<'_> {
+    type Job = (usize, VariableStore);
+    type Output = (Result<RequestRun, AppError>, output::Recorder);
+
+    fn start(&mut self, position: usize) -> Option<Self::Job> {
+        let mut var_store = self.var_store.clone();
+        for &ancestor in &self.ancestors[position] {
+            if let Some((set, removed)) = &self.changes[ancestor] {
+                var_store.merge_globals(set, removed);
+            }
+        }
+        Some((position, var_store))
     }
-    let scheme = &url[..idx];
-    let mut chars = scheme.chars();
-    let Some(first) = chars.next() else {
-        return false;
-    };
-    if !first.is_ascii_alphabetic() {
-        return false;
-    }
-    let mut has_plus_or_dash = false;
-    let mut has_dot = false;
-    for c in chars {
-        match c {
-            'a'..='z' | 'A'..='Z' | '0'..='9' => {}
-            '+' | '-' => {
-                has_plus_or_dash = true;
+
+    fn finish(&mut self, position: usize, (run, recorder): Self::Output) -> bool {
+        recorder.replay(self.reporter);
+        let mut run = match run {
+            Ok(run) => run,
+            Err(e) => {
+                self.error.get_or_insert(e);
+                return false;
             }
-            '.' => {
-                has_dot = true;
+        };
+
+        let mut proceed = true;
+        if let Some(flow) = run.flow.take() {
+            // What a skip or jump affects would depend on which requests had started
+            let unsupported = if flow.jump_to.is_some() {
+                Some("client.jump")
+            } else if !flow.skip.is_empty() {
+                Some("client.skip")
+            } else {
+                None
+            };
+            if flow.exit {
+                self.reporter.notice("Run stopped by client.exit()");
+                self.outcome = FileOutcome::Exited;
+                proceed = false;
+            } else if let Some(call) = unsupported {
+                let message = format!("{call} is not supported with --parallel");
+                self.reporter.request_error(&message);
+                run.record.errors.push(message.into());
+                proceed = false;
             }
-            _ => return false,
         }
+
+        self.reporter.request_finished(&run.record);
+        self.records[position] = Some(run.record);
+        self.changes[position] = run.globals;
+        proceed
     }
-    // Heuristic: treat dotted, domain-like prefixes without + or - as missing schemes.
-    !(has_dot && !has_plus_or_dash)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ensure_http_scheme, has_url_scheme};
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
 
-    #[test]
-    fn has_url_scheme_accepts_valid_schemes() {
-        assert!(has_url_scheme("http://example.com"));
-        assert!(has_url_scheme("https://example.com"));
-        assert!(has_url_scheme("ftp://example.com"));
-        assert!(has_url_scheme("custom+v1.2-scheme://example.com"));
+    /// Answer `count` requests with `200 {}`, one connection each.
+    fn serve(count: usize) -> (u16, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            for _ in 0..count {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}")
+                    .unwrap();
+            }
+        });
+        (port, server)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("httprun-main-{name}-{}", process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        dir
     }
 
     #[test]
-    fn has_url_scheme_rejects_invalid_or_missing_schemes() {
-        assert!(!has_url_scheme("://example.com"));
-        assert!(!has_url_scheme("1http://example.com"));
-        assert!(!has_url_scheme("http:/example.com"));
-        assert!(!has_url_scheme("example.com/path"));
-        assert!(!has_url_scheme("example.com://path"));
+    fn modules_are_evaluated_once_per_run() {
+        let (port, server) = serve(2);
+        let dir = temp_dir("modules");
+        std::fs::write(
+            dir.join("lib/counter.js"),
+            "let count = 0;\nmodule.exports = { bump: () => ++count };\n",
+        )
+        .unwrap();
+        let files: Vec<PathBuf> = [1, 2]
+            .iter()
+            .map(|n| {
+                let file = dir.join(format!("{n}.http"));
+                let http = format!(
+                    "GET http://127.0.0.1:{port}/{n}\n\n> {{%\n    const counter = require(\"./lib/counter\");\n    client.test(\"bump\", () => client.assert(counter.bump() === {n}));\n%}}\n"
+                );
+                std::fs::write(&file, http).unwrap();
+                file
+            })
+            .collect();
+
+        let cli = Cli::parse_from(["httprun".as_ref(), files[0].as_os_str(), files[1].as_os_str()]);
+        let mut recorder = output::Recorder::default();
+        assert!(run(cli, &mut recorder).unwrap());
+        server.join().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
     #[test]
-    fn ensure_http_scheme_only_prepends_when_missing() {
-        assert_eq!(
-            ensure_http_scheme("https://example.com"),
-            "https://example.com"
-        );
-        assert_eq!(
-            ensure_http_scheme("ftp://example.com"),
-            "ftp://example.com"
-        );
-        assert_eq!(
-            ensure_http_scheme("example.com/path"),
-            "https://example.com/path"
+    fn parallel_files_reject_client_skip() {
+        let (port, server) = serve(1);
+        let dir = temp_dir("parallel-skip");
+        let file = dir.join("skip.http");
+        let http = format!(
+            "# @parallel\n\n### Login\nGET http://127.0.0.1:{port}/login\n\n> {{%\n    client.skip(\"profile\");\n%}}\n\n### Profile\n# @depends login\nGET http://127.0.0.1:{port}/me\n"
         );
+        std::fs::write(&file, http).unwrap();
+
+        let cli = Cli::parse_from(["httprun".as_ref(), "--parallel".as_ref(), "2".as_ref(), file.as_os_str()]);
+        let mut recorder = output::Recorder::default();
+        assert!(!run(cli, &mut recorder).unwrap());
+        server.join().unwrap();
+        let events = format!("{recorder:?}");
+        assert!(events.contains("client.skip is not supported with --parallel"), "{events}");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn only_one_output_has_stdout() {
+        let console = |args: &[&str]| {
+            console_output(&Cli::parse_from(["httprun", "api.http"].iter().chain(args)))
+        };
+        let text = Some(output::OutputFormat::Text);
+        assert_eq!(console(&[]), Ok(text));
+        assert_eq!(console(&["--report", "junit=out.xml"]), Ok(text));
+        // The report takes stdout, so the console only prints errors (to stderr)
+        assert_eq!(console(&["--report", "tap"]), Ok(None));
+        assert_eq!(console(&["--report", "tap=-", "--report", "html=out.html"]), Ok(None));
+        assert_eq!(console(&["--report", "tap", "--dry-run"]), Ok(text));
+        assert!(console(&["--report", "tap", "--output", "jsonl"]).is_err());
+        assert!(console(&["--report", "tap", "--report", "teamcity=-"]).is_err());
         assert_eq!(
-            ensure_http_scheme("  example.com  "),
-            "https://example.com"
+            console(&["--report", "tap=report.tap", "--output", "json"]),
+            Ok(Some(output::OutputFormat::Json))
         );
     }
+
+    #[test]
+    fn reports_include_the_error_that_stopped_a_file() {
+        let dir = temp_dir("file-error");
+        let file = dir.join("broken.http");
+        std::fs::write(&file, "GET http://127.0.0.1:1/\n?? status ~ 200\n").unwrap();
+        let junit = dir.join("report.xml");
+        let report = format!("junit={}", junit.display());
+
+        let cli = Cli::parse_from(["httprun".as_ref(), file.as_os_str(), "--report".as_ref(), report.as_ref()]);
+        let err = run(cli, &mut output::Recorder::default()).unwrap_err();
+        assert!(matches!(err, AppError::Parse { line: 2, .. }), "{err}");
+        let xml = std::fs::read_to_string(&junit).unwrap();
+        assert!(xml.contains(r#"errors="1""#), "{xml}");
+        assert!(xml.contains("<error message=\"Parse error at line 2"), "{xml}");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn index_past_the_end_fails_only_the_shorter_files() {
+        let dir = temp_dir("index-range");
+        let short = dir.join("short.http");
+        let long = dir.join("long.http");
+        std::fs::write(&short, "GET http://127.0.0.1:1/only
+").unwrap();
+        std::fs::write(&long, "GET http://127.0.0.1:1/first
+
+###
+GET http://127.0.0.1:1/second
+").unwrap();
+
+        let cli = Cli::parse_from(["httprun".as_ref(), "--index".as_ref(), "2".as_ref(), short.as_os_str()]);
+        let err = run(cli, &mut output::Recorder::default()).unwrap_err();
+        assert!(matches!(err, AppError::Usage(_)), "{err}");
+
+        let cli = Cli::parse_from([
+            "httprun".as_ref(),
+            "--dry-run".as_ref(),
+            "--index".as_ref(),
+            "2".as_ref(),
+            short.as_os_str(),
+            long.as_os_str(),
+        ]);
+        let mut recorder = output::Recorder::default();
+        assert!(run(cli, &mut recorder).unwrap());
+        let events = format!("{recorder:?}");
+        assert!(events.contains("short.http: Usage error: Index 2 out of range (1-1)"), "{events}");
+        assert!(events.contains("/second") && !events.contains("/first"), "{events}");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,233 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::http::HttpResponse;
+use crate::js::{LogLine, TestResult};
+use crate::output::{Reporter, Summary};
+use crate::parser::ParsedRequest;
+use crate::report::{RequestReport, first_line};
+
+/// Whether httprun is running as a GitHub Actions step.
+pub fn detected() -> bool {
+    std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true")
+}
+
+/// Wraps the `--output` reporter when running in GitHub Actions: failed tests
+/// and request errors become `::error` workflow commands, shown as annotations
+/// on the `.http` file, and a results table is appended to the job summary
+/// (`GITHUB_STEP_SUMMARY`). Annotations are only printed alongside text
+/// output, so JSON on stdout stays parseable.
+pub struct GithubReporter {
+    inner: Box<dyn Reporter>,
+    annotate: bool,
+    step_summary: Option<PathBuf>,
+    file: Option<PathBuf>,
+    /// Markdown table rows, grouped by `.http` file.
+    sections: Vec<(Option<PathBuf>, Vec<String>)>,
+}
+
+impl GithubReporter {
+    pub fn new(inner: Box<dyn Reporter>, annotate: bool) -> Self {
+        Self {
+            inner,
+            annotate,
+            step_summary: std::env::var_os("GITHUB_STEP_SUMMARY")
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            file: None,
+            sections: Vec::new(),
+        }
+    }
+
+    fn annotate(&self, record: &RequestReport) {
+        if !self.annotate {
+            return;
+        }
+        let file = self
+            .file
+            .as_ref()
+            .map(|f| f.display().to_string())
+            .unwrap_or_default();
+        for test in record.tests.iter().filter(|t| !t.passed) {
+            let line = test.failure_line().unwrap_or(record.line);
+            let title = format!("{} › {}", record.name, test.name);
+            let message = test.failure_message().unwrap_or_default();
+            println!("{}", error_command(&file, line, &title, &message));
+        }
+        for error in &record.errors {
+            let line = error.line.unwrap_or(record.line);
+            println!("{}", error_command(&file, line, &record.name, &error.message));
+        }
+    }
+
+    fn add_row(&mut self, record: &RequestReport) {
+        let passed = record.tests.iter().filter(|t| t.passed).count();
+        let (icon, tests) = if record.skipped {
+            ("⏭️", "skipped".to_string())
+        } else if !record.errors.is_empty() {
+            ("❌", format!("error: {}", first_line(&record.errors[0].message)))
+        } else if passed < record.tests.len() {
+            ("❌", format!("{passed}/{} passed", record.tests.len()))
+        } else if record.tests.is_empty() {
+            ("✅", "—".to_string())
+        } else {
+            ("✅", format!("{passed}/{} passed", record.tests.len()))
+        };
+        let (status, time) = match &record.response {
+            Some(response) => (response.status.to_string(), format!("{} ms", response.elapsed_ms)),
+            None => ("—".to_string(), "—".to_string()),
+        };
+        let row = format!(
+            "| {icon} | {} | `{} {}` | {status} | {} | {time} |",
+            cell(&record.name),
+            record.method,
+            cell(&record.url),
+            cell(&tests)
+        );
+        match self.sections.last_mut() {
+            Some((file, rows)) if *file == self.file => rows.push(row),
+            _ => self.sections.push((self.file.clone(), vec![row])),
+        }
+    }
+
+    fn write_step_summary(&self, summary: &Summary) -> std::io::Result<()> {
+        let Some(path) = &self.step_summary else {
+            return Ok(());
+        };
+        let mut markdown = format!(
+            "### httprun {}\n\n**{} requests · {} passed · {} failed · {} errors**\n",
+            if summary.success() { "✅ passed" } else { "❌ failed" },
+            summary.requests,
+            summary.passed,
+            summary.failed,
+            summary.errors
+        );
+        for (file, rows) in &self.sections {
+            if let Some(file) = file {
+                let _ = write!(markdown, "\n#### `{}`\n", file.display());
+            }
+            markdown.push_str("\n| | Request | URL | Status | Tests | Time |\n|---|---|---|---|---|---|\n");
+            for row in rows {
+                markdown.push_str(row);
+                markdown.push('\n');
+            }
+        }
+        let mut out = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        out.write_all(markdown.as_bytes())
+    }
+}
+
+impl Reporter for GithubReporter {
+    fn run_started(&mut self, files: &[PathBuf]) {
+        self.inner.run_started(files);
+    }
+
+    fn file_started(&mut self, file: &Path) {
+        self.file = Some(file.to_path_buf());
+        self.inner.file_started(file);
+    }
+
+    fn request_started(&mut self, index: usize, request: &ParsedRequest) {
+        self.inner.request_started(index, request);
+    }
+
+    fn request_skipped(&mut self, index: usize, request: &ParsedRequest) {
+        self.inner.request_skipped(index, request);
+    }
+
+    fn response_received(&mut self, response: &HttpResponse) {
+        self.inner.response_received(response);
+    }
+
+    fn test_results(&mut self, results: &[TestResult]) {
+        self.inner.test_results(results);
+    }
+
+    fn logs(&mut self, logs: &[LogLine]) {
+        self.inner.logs(logs);
+    }
+
+    fn request_error(&mut self, message: &str) {
+        self.inner.request_error(message);
+    }
+
+    fn error(&mut self, message: &str) {
+        self.inner.error(message);
+        if self.annotate {
+            println!("::error::{}", escape_data(message));
+        }
+    }
+
+    fn notice(&mut self, message: &str) {
+        self.inner.notice(message);
+    }
+
+    fn request_finished(&mut self, record: &RequestReport) {
+        self.inner.request_finished(record);
+        self.annotate(record);
+        self.add_row(record);
+    }
+
+    fn dry_run_started(&mut self, count: usize, file: &Path) {
+        self.inner.dry_run_started(count, file);
+    }
+
+    fn dry_run_request(&mut self, index: usize, request: &ParsedRequest) {
+        self.inner.dry_run_request(index, request);
+    }
+
+    fn summary(&mut self, summary: &Summary) {
+        self.inner.summary(summary);
+        if let Err(e) = self.write_step_summary(summary) {
+            self.inner.notice(&format!("Could not write the GitHub job summary: {e}"));
+        }
+    }
+
+    fn finish(&mut self) {
+        self.inner.finish();
+    }
+}
+
+/// An `::error` workflow command that annotates `file` at `line`.
+fn error_command(file: &str, line: usize, title: &str, message: &str) -> String {
+    let mut properties = Vec::new();
+    if !file.is_empty() {
+        properties.push(format!("file={}", escape_property(file)));
+        if line > 0 {
+            properties.push(format!("line={line}"));
+        }
+    }
+    properties.push(format!("title={}", escape_property(title)));
+    format!("::error {}::{}", properties.join(","), escape_data(message))
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Markdown table cells can't hold pipes or line breaks.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_commands_escape_properties_and_data() {
+        assert_eq!(
+            error_command("api/users.http", 12, "Create, user › status: 201", "expected 201\nbut got 50%"),
+            "::error file=api/users.http,line=12,title=Create%2C user › status%3A 201::expected 201%0Abut got 50%25"
+        );
+        assert_eq!(error_command("", 3, "Login", "boom"), "::error title=Login::boom");
+    }
+}
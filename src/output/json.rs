@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value, json};
+
+use crate::js::{LogLevel, TestResult};
+use crate::load::LoadStats;
+use crate::output::{Reporter, Summary};
+use crate::parser::ParsedRequest;
+use crate::report::RequestReport;
+
+/// Machine-readable output. As a document (`--output json`) everything is
+/// collected and printed as one object when the run ends; as JSON Lines
+/// (`--output jsonl`) each request, error and the summary is printed on its
+/// own line as soon as it is known, tagged with a `type`.
+pub struct JsonReporter {
+    lines: bool,
+    file: Option<PathBuf>,
+    requests: Vec<Value>,
+    errors: Vec<String>,
+    summary: Option<Summary>,
+}
+
+impl JsonReporter {
+    pub fn new(lines: bool) -> Self {
+        Self {
+            lines,
+            file: None,
+            requests: Vec::new(),
+            errors: Vec::new(),
+            summary: None,
+        }
+    }
+
+    fn emit(&mut self, kind: &str, mut value: Value) {
+        if self.lines {
+            if let Value::Object(map) = &mut value {
+                map.insert("type".to_string(), Value::from(kind));
+            }
+            println!("{value}");
+        }
+    }
+
+    fn file_name(&self) -> Value {
+        self.file
+            .as_ref()
+            .map_or(Value::Null, |f| Value::from(f.display().to_string()))
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn file_started(&mut self, file: &Path) {
+        self.file = Some(file.to_path_buf());
+    }
+
+    fn error(&mut self, message: &str) {
+        self.emit("error", json!({ "message": message }));
+        self.errors.push(message.to_string());
+    }
+
+    fn request_finished(&mut self, record: &RequestReport) {
+        let mut value = request_json(record);
+        value["file"] = self.file_name();
+        self.emit("request", value.clone());
+        self.requests.push(value);
+    }
+
+    fn dry_run_request(&mut self, index: usize, request: &ParsedRequest) {
+        let mut value = request_json(&RequestReport::new(index, request));
+        value["file"] = self.file_name();
+        value["dry_run"] = Value::Bool(true);
+        self.emit("request", value.clone());
+        self.requests.push(value);
+    }
+
+    fn summary(&mut self, summary: &Summary) {
+        self.emit("summary", summary_json(summary));
+        self.summary = Some(*summary);
+    }
+
+    fn finish(&mut self) {
+        if self.lines {
+            return;
+        }
+        let document = json!({
+            "requests": std::mem::take(&mut self.requests),
+            "errors": std::mem::take(&mut self.errors),
+            "summary": self.summary.as_ref().map_or(Value::Null, summary_json),
+        });
+        println!("{}", serde_json::to_string_pretty(&document).unwrap_or_default());
+    }
+}
+
+fn summary_json(summary: &Summary) -> Value {
+    json!({
+        "files": summary.files,
+        "requests": summary.requests,
+        "passed": summary.passed,
+        "failed": summary.failed,
+        "errors": summary.errors,
+        "success": summary.success(),
+    })
+}
+
+fn request_json(record: &RequestReport) -> Value {
+    let headers: Map<String, Value> = record
+        .headers
+        .iter()
+        .map(|h| (h.name.clone(), Value::from(h.value.clone())))
+        .collect();
+
+    let response = record.response.as_ref().map_or(Value::Null, |response| {
+        let mut names: Vec<&String> = response.headers.keys().collect();
+        names.sort();
+        let headers: Map<String, Value> = names
+            .into_iter()
+            .map(|name| (name.clone(), json!(response.headers[name])))
+            .collect();
+        json!({
+            "status": response.status,
+            "headers": headers,
+            "body": response
+                .body_json
+                .clone()
+                .unwrap_or_else(|| Value::from(response.body_raw.clone())),
+            "elapsed_ms": response.elapsed_ms as u64,
+        })
+    });
+
+    let mut value = json!({
+        "index": record.index,
+        "name": record.name,
+        "skipped": record.skipped,
+        "request": {
+            "method": record.method,
+            "url": record.url,
+            "headers": headers,
+            "body": record.body,
+        },
+        "response": response,
+        "logs": record.logs.iter().map(|log| json!({
+            "level": level_name(log.level),
+            "message": log.message,
+        })).collect::<Vec<_>>(),
+        "tests": record.tests.iter().map(test_json).collect::<Vec<_>>(),
+        "errors": record.errors.iter().map(|e| &e.message).collect::<Vec<_>>(),
+    });
+    if let Some(stats) = &record.load {
+        value["load"] = load_json(stats);
+    }
+    value
+}
+
+fn load_json(stats: &LoadStats) -> Value {
+    let statuses: Map<String, Value> = stats
+        .statuses
+        .iter()
+        .map(|(status, count)| (status.to_string(), Value::from(*count)))
+        .collect();
+    json!({
+        "iterations": stats.iterations,
+        "concurrency": stats.concurrency,
+        "duration_ms": stats.duration.as_millis() as u64,
+        "throughput": stats.throughput(),
+        "errors": stats.errors,
+        "error_rate": stats.error_rate(),
+        "failed_checks": stats.failed_checks,
+        "latency_ms": {
+            "p50": stats.p50_ms as u64,
+            "p90": stats.p90_ms as u64,
+            "p99": stats.p99_ms as u64,
+            "max": stats.max_ms as u64,
+        },
+        "statuses": statuses,
+    })
+}
+
+fn test_json(test: &TestResult) -> Value {
+    json!({
+        "name": test.name,
+        "passed": test.passed,
+        "duration_ms": test.duration.as_millis() as u64,
+        "assertions": test.assertions.iter().map(|a| json!({
+            "passed": a.passed,
+            "message": a.message,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+fn level_name(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Log => "log",
+        LogLevel::Info => "info",
+        LogLevel::Warn => "warn",
+        LogLevel::Error => "error",
+        LogLevel::Debug => "debug",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_http_file;
+
+    #[test]
+    fn request_record_as_json() {
+        let parsed = parse_http_file(
+            "### Create\nPOST https://example.com/items\nContent-Type: application/json\n\n{\"a\": 1}\n",
+        )
+        .unwrap();
+        let mut record = RequestReport::new(1, &parsed.requests[0]);
+        record.tests.push(TestResult::fail("created", "expected 201"));
+        record.errors.push("Handler error: boom".to_string().into());
+
+        let value = request_json(&record);
+        assert_eq!(value["name"], "Create");
+        assert_eq!(value["request"]["method"], "POST");
+        assert_eq!(value["request"]["headers"]["Content-Type"], "application/json");
+        assert_eq!(value["request"]["body"], "{\"a\": 1}");
+        assert_eq!(value["response"], Value::Null);
+        assert_eq!(value["tests"][0]["assertions"][0]["message"], "expected 201");
+        assert_eq!(value["errors"][0], "Handler error: boom");
+    }
+}
@@ -0,0 +1,114 @@
+mod github;
+mod json;
+mod quiet;
+mod recorder;
+mod text;
+
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+
+use crate::http::HttpResponse;
+use crate::js::{LogLine, TestResult};
+use crate::parser::ParsedRequest;
+use crate::report::RequestReport;
+
+pub use recorder::Recorder;
+
+/// How results are written to stdout (`--output`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored, human-readable text
+    Text,
+    /// One JSON document, written when the run ends
+    Json,
+    /// One JSON object per line, streamed as requests finish
+    Jsonl,
+}
+
+/// Totals printed at the end of a run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Summary {
+    /// `.http` files that ran.
+    pub files: usize,
+    pub requests: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub errors: usize,
+}
+
+impl Summary {
+    /// Count a finished request.
+    pub fn add(&mut self, record: &RequestReport) {
+        if record.skipped {
+            return;
+        }
+        let passed = record.tests.iter().filter(|t| t.passed).count();
+        self.requests += 1;
+        self.passed += passed;
+        self.failed += record.tests.len() - passed;
+        self.errors += record.errors.len();
+    }
+
+    pub fn success(&self) -> bool {
+        self.failed == 0 && self.errors == 0
+    }
+}
+
+/// Receives everything that happens during a run, in order. The text reporter
+/// prints each event as it arrives; the JSON reporters only need the finished
+/// request records and the summary, so most events default to doing nothing.
+pub trait Reporter {
+    /// The run is starting with these `.http` files.
+    fn run_started(&mut self, _files: &[PathBuf]) {}
+
+    /// A `.http` file is about to run.
+    fn file_started(&mut self, _file: &Path) {}
+
+    fn request_started(&mut self, _index: usize, _request: &ParsedRequest) {}
+
+    fn request_skipped(&mut self, _index: usize, _request: &ParsedRequest) {}
+
+    fn response_received(&mut self, _response: &HttpResponse) {}
+
+    fn test_results(&mut self, _results: &[TestResult]) {}
+
+    fn logs(&mut self, _logs: &[LogLine]) {}
+
+    /// A request failed; the message is also in its [`RequestReport`].
+    fn request_error(&mut self, _message: &str) {}
+
+    /// The run failed outside any request.
+    fn error(&mut self, message: &str);
+
+    fn notice(&mut self, _message: &str) {}
+
+    /// A request is done; `record` holds everything reported about it.
+    fn request_finished(&mut self, _record: &RequestReport) {}
+
+    fn dry_run_started(&mut self, _count: usize, _file: &Path) {}
+
+    fn dry_run_request(&mut self, index: usize, request: &ParsedRequest);
+
+    fn summary(&mut self, summary: &Summary);
+
+    /// The run is over (successfully or not); flush anything buffered.
+    fn finish(&mut self) {}
+}
+
+/// The reporter for `--output`, wrapped to add annotations and a job summary
+/// when running in GitHub Actions. Without a format, stdout belongs to a
+/// `--report` and only errors are printed, to stderr.
+pub fn reporter(format: Option<OutputFormat>, verbose: bool) -> Box<dyn Reporter> {
+    let reporter: Box<dyn Reporter> = match format {
+        Some(OutputFormat::Text) => Box::new(text::TextReporter::new(verbose)),
+        Some(OutputFormat::Json) => Box::new(json::JsonReporter::new(false)),
+        Some(OutputFormat::Jsonl) => Box::new(json::JsonReporter::new(true)),
+        None => Box::new(quiet::QuietReporter),
+    };
+    if github::detected() {
+        Box::new(github::GithubReporter::new(reporter, format == Some(OutputFormat::Text)))
+    } else {
+        reporter
+    }
+}
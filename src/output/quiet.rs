@@ -0,0 +1,16 @@
+use crate::output::{Reporter, Summary, text};
+use crate::parser::ParsedRequest;
+
+/// Prints nothing but errors, to stderr. Used when a `--report` is written to
+/// stdout, so the report isn't mixed with console output.
+pub struct QuietReporter;
+
+impl Reporter for QuietReporter {
+    fn error(&mut self, message: &str) {
+        text::print_error(message);
+    }
+
+    fn dry_run_request(&mut self, _index: usize, _request: &ParsedRequest) {}
+
+    fn summary(&mut self, _summary: &Summary) {}
+}
@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+
+use crate::http::HttpResponse;
+use crate::js::{LogLine, TestResult};
+use crate::output::{Reporter, Summary};
+use crate::parser::ParsedRequest;
+use crate::report::RequestReport;
+
+/// A reporter event, owned so it can cross threads.
+#[derive(Debug)]
+enum Event {
+    RunStarted(Vec<PathBuf>),
+    FileStarted(PathBuf),
+    RequestStarted(usize, ParsedRequest),
+    RequestSkipped(usize, ParsedRequest),
+    ResponseReceived(HttpResponse),
+    TestResults(Vec<TestResult>),
+    Logs(Vec<LogLine>),
+    RequestError(String),
+    Error(String),
+    Notice(String),
+    RequestFinished(Box<RequestReport>),
+    DryRunStarted(usize, PathBuf),
+    DryRunRequest(usize, ParsedRequest),
+    Summary(Summary),
+}
+
+/// Buffers events so they can be replayed into another reporter later. Used
+/// by `--parallel`, where each request reports into its own recorder on a
+/// worker thread and the output is replayed request by request instead of
+/// interleaving, and by `--repeat`, which drops the output of all but the
+/// first iteration.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    events: Vec<Event>,
+}
+
+impl Recorder {
+    /// Send every recorded event to `reporter`, in the order they arrived.
+    pub fn replay(self, reporter: &mut dyn Reporter) {
+        for event in self.events {
+            match event {
+                Event::RunStarted(files) => reporter.run_started(&files),
+                Event::FileStarted(file) => reporter.file_started(&file),
+                Event::RequestStarted(index, request) => reporter.request_started(index, &request),
+                Event::RequestSkipped(index, request) => reporter.request_skipped(index, &request),
+                Event::ResponseReceived(response) => reporter.response_received(&response),
+                Event::TestResults(results) => reporter.test_results(&results),
+                Event::Logs(logs) => reporter.logs(&logs),
+                Event::RequestError(message) => reporter.request_error(&message),
+                Event::Error(message) => reporter.error(&message),
+                Event::Notice(message) => reporter.notice(&message),
+                Event::RequestFinished(record) => reporter.request_finished(&record),
+                Event::DryRunStarted(count, file) => reporter.dry_run_started(count, &file),
+                Event::DryRunRequest(index, request) => reporter.dry_run_request(index, &request),
+                Event::Summary(summary) => reporter.summary(&summary),
+            }
+        }
+    }
+}
+
+impl Reporter for Recorder {
+    fn run_started(&mut self, files: &[PathBuf]) {
+        self.events.push(Event::RunStarted(files.to_vec()));
+    }
+
+    fn file_started(&mut self, file: &Path) {
+        self.events.push(Event::FileStarted(file.to_path_buf()));
+    }
+
+    fn request_started(&mut self, index: usize, request: &ParsedRequest) {
+        self.events.push(Event::RequestStarted(index, request.clone()));
+    }
+
+    fn request_skipped(&mut self, index: usize, request: &ParsedRequest) {
+        self.events.push(Event::RequestSkipped(index, request.clone()));
+    }
+
+    fn response_received(&mut self, response: &HttpResponse) {
+        self.events.push(Event::ResponseReceived(response.clone()));
+    }
+
+    fn test_results(&mut self, results: &[TestResult]) {
+        self.events.push(Event::TestResults(results.to_vec()));
+    }
+
+    fn logs(&mut self, logs: &[LogLine]) {
+        self.events.push(Event::Logs(logs.to_vec()));
+    }
+
+    fn request_error(&mut self, message: &str) {
+        self.events.push(Event::RequestError(message.to_string()));
+    }
+
+    fn error(&mut self, message: &str) {
+        self.events.push(Event::Error(message.to_string()));
+    }
+
+    fn notice(&mut self, message: &str) {
+        self.events.push(Event::Notice(message.to_string()));
+    }
+
+    fn request_finished(&mut self, record: &RequestReport) {
+        self.events.push(Event::RequestFinished(Box::new(record.clone())));
+    }
+
+    fn dry_run_started(&mut self, count: usize, file: &Path) {
+        self.events.push(Event::DryRunStarted(count, file.to_path_buf()));
+    }
+
+    fn dry_run_request(&mut self, index: usize, request: &ParsedRequest) {
+        self.events.push(Event::DryRunRequest(index, request.clone()));
+    }
+
+    fn summary(&mut self, summary: &Summary) {
+        self.events.push(Event::Summary(*summary));
+    }
+}
@@ -0,0 +1,345 @@
+use std::time::Duration;
+
+use colored::Colorize;
+
+use std::path::{Path, PathBuf};
+
+use crate::http::HttpResponse;
+use crate::js::{LogLevel, LogLine, TestResult};
+use crate::load::LoadStats;
+use crate::output::{Reporter, Summary};
+use crate::parser::ParsedRequest;
+use crate::report::RequestReport;
+
+/// Colored, human-readable output.
+pub struct TextReporter {
+    /// Also print request and response headers and bodies.
+    verbose: bool,
+    /// Head each file's requests with its path (when running several files).
+    show_files: bool,
+}
+
+impl TextReporter {
+    pub fn new(verbose: bool) -> Self {
+        Self {
+            verbose,
+            show_files: false,
+        }
+    }
+}
+
+impl Reporter for TextReporter {
+    fn run_started(&mut self, files: &[PathBuf]) {
+        self.show_files = files.len() > 1;
+    }
+
+    fn file_started(&mut self, file: &Path) {
+        if self.show_files {
+            println!("\n{}", format!("━━ {}", file.display()).magenta().bold());
+        }
+    }
+
+    fn request_started(&mut self, index: usize, request: &ParsedRequest) {
+        print_request_header(index, request);
+        if self.verbose {
+            print_verbose_request(request);
+        }
+    }
+
+    fn request_skipped(&mut self, index: usize, request: &ParsedRequest) {
+        print_skipped(index, request);
+    }
+
+    fn response_received(&mut self, response: &HttpResponse) {
+        print_response_status(response);
+        if self.verbose {
+            print_verbose_response(response);
+        }
+    }
+
+    fn test_results(&mut self, results: &[TestResult]) {
+        print_test_results(results);
+    }
+
+    fn logs(&mut self, logs: &[LogLine]) {
+        print_log_output(logs);
+    }
+
+    fn request_error(&mut self, message: &str) {
+        print_error(message);
+    }
+
+    fn error(&mut self, message: &str) {
+        print_error(message);
+    }
+
+    fn notice(&mut self, message: &str) {
+        print_notice(message);
+    }
+
+    fn request_finished(&mut self, record: &RequestReport) {
+        if let Some(stats) = &record.load {
+            print_load_stats(stats);
+        }
+    }
+
+    fn dry_run_started(&mut self, count: usize, file: &Path) {
+        println!("Dry run: {} request(s) from {}", count, file.display());
+    }
+
+    fn dry_run_request(&mut self, index: usize, request: &ParsedRequest) {
+        print_dry_run_request(index, request);
+    }
+
+    fn summary(&mut self, summary: &Summary) {
+        print_summary(summary);
+    }
+}
+
+fn print_separator() {
+    println!("{}", "─".repeat(60).dimmed());
+}
+
+fn print_request_header(index: usize, request: &ParsedRequest) {
+    let name = request
+        .name
+        .as_deref()
+        .unwrap_or("Unnamed request");
+    println!(
+        "\n{} {}",
+        format!("[{}]", index).cyan().bold(),
+        name.cyan().bold()
+    );
+    println!(
+        "  {} {}",
+        request.method.as_str().white().bold(),
+        request.url.white()
+    );
+}
+
+fn print_response_status(response: &HttpResponse) {
+    let status_str = format!("{}", response.status);
+    let colored_status = match response.status {
+        200..=299 => status_str.green().bold(),
+        300..=399 => status_str.yellow().bold(),
+        _ => status_str.red().bold(),
+    };
+    println!(
+        "  {} {} ({}ms)",
+        "→".dimmed(),
+        colored_status,
+        response.elapsed_ms
+    );
+}
+
+fn print_response_body(response: &HttpResponse) {
+    if response.body_raw.is_empty() {
+        return;
+    }
+
+    // Pretty-print JSON if available, otherwise raw
+    let display = if let Some(json) = &response.body_json {
+        serde_json::to_string_pretty(json).unwrap_or_else(|_| response.body_raw.clone())
+    } else {
+        response.body_raw.clone()
+    };
+
+    // Truncate long bodies
+    let lines: Vec<&str> = display.lines().collect();
+    if lines.len() > 30 {
+        for line in &lines[..30] {
+            println!("  {}", line.dimmed());
+        }
+        println!("  {}", format!("... ({} more lines)", lines.len() - 30).dimmed());
+    } else {
+        for line in &lines {
+            println!("  {}", line.dimmed());
+        }
+    }
+}
+
+fn print_verbose_request(request: &ParsedRequest) {
+    if !request.headers.is_empty() {
+        println!("  {}", "Request Headers:".dimmed());
+        for h in &request.headers {
+            println!("    {}: {}", h.name.dimmed(), h.value.dimmed());
+        }
+    }
+    if let Some(body) = &request.body {
+        println!("  {}", "Request Body:".dimmed());
+        for line in body.lines() {
+            println!("    {}", line.dimmed());
+        }
+    }
+}
+
+fn print_verbose_response(response: &HttpResponse) {
+    println!("  {}", "Response Headers:".dimmed());
+    for (name, values) in &response.headers {
+        for v in values {
+            println!("    {}: {}", name.dimmed(), v.dimmed());
+        }
+    }
+    println!("  {}", "Response Body:".dimmed());
+    print_response_body(response);
+}
+
+fn print_test_results(results: &[TestResult]) {
+    for result in results {
+        let duration = format_test_duration(result.duration);
+        if result.passed {
+            println!("  {} {}{}", "PASS".green().bold(), result.name, duration);
+            continue;
+        }
+
+        let failures: Vec<&str> = result
+            .assertions
+            .iter()
+            .filter(|a| !a.passed)
+            .map(|a| a.message.as_str())
+            .collect();
+        match failures.as_slice() {
+            [] | [_] => println!(
+                "  {} {}{} — {}",
+                "FAIL".red().bold(),
+                result.name,
+                duration,
+                indent_continuation(failures.first().copied().unwrap_or("Assertion failed")).red()
+            ),
+            _ => {
+                println!("  {} {}{}", "FAIL".red().bold(), result.name, duration);
+                for message in failures {
+                    println!("    {} {}", "✗".red(), indent_continuation(message).red());
+                }
+            }
+        }
+    }
+}
+
+/// Test durations are only worth showing once they reach a millisecond.
+fn format_test_duration(duration: Duration) -> String {
+    let ms = duration.as_millis();
+    if ms == 0 {
+        String::new()
+    } else {
+        format!(" {}", format!("({ms}ms)").dimmed())
+    }
+}
+
+fn print_log_output(logs: &[LogLine]) {
+    for log in logs {
+        let label = match log.level {
+            LogLevel::Log => "LOG  ".blue().bold(),
+            LogLevel::Info => "INFO ".cyan().bold(),
+            LogLevel::Warn => "WARN ".yellow().bold(),
+            LogLevel::Error => "ERROR".red().bold(),
+            LogLevel::Debug => "DEBUG".dimmed().bold(),
+        };
+        let mut lines = log.message.lines();
+        println!("  {} {}", label, lines.next().unwrap_or_default());
+        // Continuation lines of pretty-printed objects line up under the text
+        for line in lines {
+            println!("        {line}");
+        }
+    }
+}
+
+fn print_load_stats(stats: &LoadStats) {
+    println!(
+        "  {} {} requests in {:.2}s, {} at a time ({:.1} req/s)",
+        "LOAD ".magenta().bold(),
+        stats.iterations,
+        stats.duration.as_secs_f64(),
+        stats.concurrency,
+        stats.throughput()
+    );
+    println!(
+        "        latency p50 {}ms · p90 {}ms · p99 {}ms · max {}ms",
+        stats.p50_ms, stats.p90_ms, stats.p99_ms, stats.max_ms
+    );
+    let errors = format!("errors {} ({:.1}%)", stats.errors, stats.error_rate() * 100.0);
+    let statuses: Vec<String> = stats
+        .statuses
+        .iter()
+        .map(|(status, count)| format!("{status} ×{count}"))
+        .collect();
+    println!(
+        "        {} · status {}",
+        if stats.errors == 0 { errors.green() } else { errors.red() },
+        if statuses.is_empty() { "—".to_string() } else { statuses.join(", ") }
+    );
+    if stats.failed_checks > 0 {
+        println!("        {}", format!("failed checks {}", stats.failed_checks).red());
+    }
+}
+
+fn print_skipped(index: usize, request: &ParsedRequest) {
+    let name = request
+        .name
+        .as_deref()
+        .unwrap_or("Unnamed request");
+    println!(
+        "\n{} {} {}",
+        format!("[{}]", index).dimmed(),
+        name.dimmed(),
+        "SKIPPED".yellow().bold()
+    );
+}
+
+fn print_notice(msg: &str) {
+    println!("  {} {}", "NOTE".yellow().bold(), msg);
+}
+
+pub(super) fn print_error(msg: &str) {
+    eprintln!("  {} {}", "ERROR".red().bold(), indent_continuation(msg).red());
+}
+
+/// Indent every line after the first so multi-line messages (e.g. source
+/// excerpts) stay aligned under their label.
+fn indent_continuation(msg: &str) -> String {
+    msg.replace('\n', "\n    ")
+}
+
+fn print_summary(totals: &Summary) {
+    println!();
+    print_separator();
+
+    let mut summary = format!(
+        "Requests: {}  |  Tests passed: {}  |  Tests failed: {}  |  Errors: {}",
+        totals.requests, totals.passed, totals.failed, totals.errors
+    );
+    if totals.files > 1 {
+        summary = format!("Files: {}  |  {summary}", totals.files);
+    }
+
+    if totals.success() {
+        println!("{}", summary.green().bold());
+    } else {
+        println!("{}", summary.red().bold());
+    }
+}
+
+fn print_dry_run_request(index: usize, request: &ParsedRequest) {
+    print_request_header(index, request);
+
+    if !request.headers.is_empty() {
+        for h in &request.headers {
+            println!("    {}: {}", h.name, h.value);
+        }
+    }
+
+    if let Some(body) = &request.body {
+        println!();
+        for line in body.lines() {
+            println!("    {}", line);
+        }
+    }
+
+    if !request.tags.is_empty() {
+        println!("    {}", format!("(tags: {})", request.tags.join(", ")).dimmed());
+    }
+
+    if request.response_handler.is_some() {
+        println!("    {}", "(has response handler)".dimmed());
+    }
+}
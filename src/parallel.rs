@@ -0,0 +1,148 @@
+use std::collections::BTreeSet;
+use std::sync::{Mutex, mpsc};
+use std::thread;
+
+/// The calling thread's side of [`run`]: it decides what each job gets as it
+/// becomes ready and takes each result as it comes back.
+pub trait Schedule {
+    type Job: Send;
+    type Output: Send;
+
+    /// Job `index` is ready to run. `None` completes it without running it.
+    fn start(&mut self, index: usize) -> Option<Self::Job>;
+
+    /// Job `index` has finished. Returning `false` stops starting new jobs;
+    /// those already running still finish.
+    fn finish(&mut self, index: usize, output: Self::Output) -> bool;
+}
+
+/// Run jobs `0..dependencies.len()` on up to `workers` threads, each with its
+/// own state from `init`. A job starts once every job listed in its
+/// `dependencies` entry has finished; among ready jobs the lowest index starts
+/// first. `start` and `finish` are called on the calling thread, so results
+/// are handled one at a time, in completion order.
+pub fn run<S, W>(
+    dependencies: &[Vec<usize>],
+    workers: usize,
+    schedule: &mut S,
+    init: impl Fn() -> W + Sync,
+    work: impl Fn(&mut W, S::Job) -> S::Output + Sync,
+) where
+    S: Schedule,
+{
+    let count = dependencies.len();
+    let mut waiting: Vec<usize> = dependencies.iter().map(Vec::len).collect();
+    let mut dependents = vec![Vec::new(); count];
+    for (index, depends_on) in dependencies.iter().enumerate() {
+        for &dependency in depends_on {
+            dependents[dependency].push(index);
+        }
+    }
+    let mut ready: BTreeSet<usize> = (0..count).filter(|&i| waiting[i] == 0).collect();
+    let mut release = |index: usize, ready: &mut BTreeSet<usize>| {
+        for &dependent in &dependents[index] {
+            waiting[dependent] -= 1;
+            if waiting[dependent] == 0 {
+                ready.insert(dependent);
+            }
+        }
+    };
+
+    let workers = workers.clamp(1, count.max(1));
+    let (jobs, job_queue) = mpsc::channel::<(usize, S::Job)>();
+    let job_queue = Mutex::new(job_queue);
+    let (results, finished) = mpsc::channel::<(usize, S::Output)>();
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let results = results.clone();
+            let (job_queue, init, work) = (&job_queue, &init, &work);
+            scope.spawn(move || {
+                let mut state = init();
+                loop {
+                    let next = job_queue.lock().expect("job queue poisoned").recv();
+                    let Ok((index, job)) = next else { break };
+                    if results.send((index, work(&mut state, job))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(results);
+
+        let (mut running, mut stopped) = (0, false);
+        loop {
+            while !stopped && running < workers {
+                let Some(index) = ready.pop_first() else { break };
+                match schedule.start(index) {
+                    Some(job) => {
+                        jobs.send((index, job)).expect("worker threads outlive the schedule");
+                        running += 1;
+                    }
+                    None => release(index, &mut ready),
+                }
+            }
+            if running == 0 {
+                break;
+            }
+            let Ok((index, output)) = finished.recv() else { break };
+            running -= 1;
+            if !schedule.finish(index, output) {
+                stopped = true;
+            }
+            release(index, &mut ready);
+        }
+        drop(jobs);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Records start and finish order; job 3 is skipped and job 4 stops the run.
+    #[derive(Default)]
+    struct Trace {
+        started: Vec<usize>,
+        finished: Vec<usize>,
+    }
+
+    impl Schedule for Trace {
+        type Job = usize;
+        type Output = usize;
+
+        fn start(&mut self, index: usize) -> Option<usize> {
+            self.started.push(index);
+            (index != 3).then_some(index)
+        }
+
+        fn finish(&mut self, index: usize, output: usize) -> bool {
+            assert_eq!(index, output);
+            self.finished.push(index);
+            index != 4
+        }
+    }
+
+    #[test]
+    fn starts_jobs_after_their_dependencies() {
+        // 0 is slow, so 1, 3 and 4 go ahead of it; 4 stops the run before 2
+        // (waiting for 0) and 5 (waiting for 4) can start
+        let dependencies = [vec![], vec![], vec![0], vec![1], vec![3], vec![4]];
+        let mut trace = Trace::default();
+        run(&dependencies, 2, &mut trace, || (), |_, index| {
+            if index == 0 {
+                thread::sleep(Duration::from_millis(100));
+            }
+            index
+        });
+
+        assert_eq!(trace.started, [0, 1, 3, 4]);
+        assert_eq!(trace.finished, [1, 4, 0]);
+
+        let mut serial = Trace::default();
+        run(&[vec![], vec![], vec![1]], 1, &mut serial, || (), |_, index| index);
+        assert_eq!(serial.finished, [0, 1, 2]);
+    }
+}
@@ -22,6 +22,24 @@ static RESPONSE_HISTORY_RE: LazyLock<Regex> =
 static IN_PLACE_VAR_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^@(\S+)\s*=\s*(.+)$").unwrap());
 
+static SCHEMA_DIRECTIVE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:#|//)\s*@schema\s+(\S+)\s*$").unwrap());
+
+static ASSERTION_LINE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\?\?\s+(.+)$").unwrap());
+
+static SNAPSHOT_DIRECTIVE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:#|//)\s*@snapshot(?:\s+(.*))?$").unwrap());
+
+static TAG_DIRECTIVE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:#|//)\s*@tags?\s+(.+)$").unwrap());
+
+static DEPENDS_DIRECTIVE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:#|//)\s*@depends\s+(.+)$").unwrap());
+
+static PARALLEL_DIRECTIVE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:#|//)\s*@parallel\s*$").unwrap());
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum HttpMethod {
     Get,
@@ -72,6 +90,205 @@ pub struct Header {
     pub value: String,
 }
 
+/// What a `??` assertion inspects.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssertionSubject {
+    Status,
+    Header(String),
+    Body,
+    JsonPath(String),
+    Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssertionOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    NotContains,
+    Matches,
+    Exists,
+    NotExists,
+}
+
+impl AssertionOp {
+    fn from_tokens(first: &str, second: Option<&str>) -> Option<(Self, usize)> {
+        let op = match first.to_ascii_lowercase().as_str() {
+            "==" => Self::Eq,
+            "!=" => Self::Ne,
+            "<" => Self::Lt,
+            "<=" => Self::Le,
+            ">" => Self::Gt,
+            ">=" => Self::Ge,
+            "contains" => Self::Contains,
+            "matches" => Self::Matches,
+            "exists" => Self::Exists,
+            "not" => {
+                let negated = match second?.to_ascii_lowercase().as_str() {
+                    "contains" => Self::NotContains,
+                    "exists" => Self::NotExists,
+                    _ => return None,
+                };
+                return Some((negated, 2));
+            }
+            _ => return None,
+        };
+        Some((op, 1))
+    }
+
+    /// Whether the operator compares against an expected value.
+    pub fn takes_value(self) -> bool {
+        !matches!(self, Self::Exists | Self::NotExists)
+    }
+}
+
+/// A declarative `?? <subject> [length] <op> [value]` assertion, checked
+/// without running JavaScript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assertion {
+    /// The assertion as written, used as the test name.
+    pub source: String,
+    pub line: usize,
+    pub subject: AssertionSubject,
+    /// Compare the length of the value rather than the value itself.
+    pub length: bool,
+    pub op: AssertionOp,
+    pub expected: Option<String>,
+}
+
+impl Assertion {
+    fn parse(text: &str, line: usize) -> Result<Self, AppError> {
+        let error = |message: String| AppError::Parse { line, message };
+        let tokens = split_assertion_tokens(text);
+        let token = |i: usize| tokens.get(i).map(|&(start, end)| &text[start..end]);
+
+        let Some(keyword) = token(0) else {
+            return Err(error("empty assertion".to_string()));
+        };
+        let mut next = 1;
+        let mut argument = |what: &str| {
+            let value = token(next).map(str::to_string);
+            next += 1;
+            value.ok_or_else(|| error(format!("'{keyword}' assertion needs a {what}")))
+        };
+        let subject = match keyword.to_ascii_lowercase().as_str() {
+            "status" => AssertionSubject::Status,
+            "header" => AssertionSubject::Header(argument("header name")?),
+            "body" => AssertionSubject::Body,
+            "jsonpath" => AssertionSubject::JsonPath(argument("JSONPath expression")?),
+            "duration" => AssertionSubject::Duration,
+            other => {
+                return Err(error(format!(
+                    "unknown assertion subject '{other}' (expected status, header, body, jsonpath or duration)"
+                )));
+            }
+        };
+
+        let length = token(next).is_some_and(|t| t.eq_ignore_ascii_case("length"));
+        if length {
+            next += 1;
+        }
+
+        let Some(op_token) = token(next) else {
+            return Err(error(format!("assertion '{text}' is missing an operator")));
+        };
+        let Some((op, op_len)) = AssertionOp::from_tokens(op_token, token(next + 1)) else {
+            return Err(error(format!("unknown assertion operator '{op_token}'")));
+        };
+        next += op_len;
+
+        let rest = tokens.get(next).map(|&(start, _)| text[start..].trim());
+        let expected = match (op.takes_value(), rest) {
+            (true, Some(value)) => Some(unquote(value).to_string()),
+            (true, None) => return Err(error(format!("'{op_token}' needs a value to compare with"))),
+            (false, Some(extra)) => return Err(error(format!("unexpected '{extra}' after '{op_token}'"))),
+            (false, None) => None,
+        };
+
+        Ok(Self {
+            source: text.to_string(),
+            line,
+            subject,
+            length,
+            op,
+            expected,
+        })
+    }
+}
+
+/// Split on whitespace outside quotes and brackets, so JSONPath filters such as
+/// `$[?@.name == 'a b']` stay in one token. Returns byte ranges into `text`.
+fn split_assertion_tokens(text: &str) -> Vec<(usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' => quote = Some(c),
+                '[' | '(' => depth += 1,
+                ']' | ')' => depth = depth.saturating_sub(1),
+                c if c.is_whitespace() && depth == 0 => {
+                    if let Some(s) = start.take() {
+                        tokens.push((s, i));
+                    }
+                    continue;
+                }
+                _ => {}
+            },
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        tokens.push((s, text.len()));
+    }
+    tokens
+}
+
+fn unquote(value: &str) -> &str {
+    for q in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(q).and_then(|v| v.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    value
+}
+
+/// A `# @snapshot [name] [ignore=$.a,$.b]` directive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapshotDirective {
+    /// Snapshot name; defaults to the request name.
+    pub name: Option<String>,
+    /// JSONPaths of volatile values to leave out of the comparison.
+    pub ignore: Vec<String>,
+}
+
+impl SnapshotDirective {
+    fn parse(args: &str) -> Self {
+        let mut directive = Self::default();
+        let mut name_parts = Vec::new();
+        for part in args.split_whitespace() {
+            match part.strip_prefix("ignore=") {
+                Some(paths) => directive.ignore.extend(
+                    paths.split(',').filter(|p| !p.is_empty()).map(str::to_string),
+                ),
+                None => name_parts.push(part),
+            }
+        }
+        if !name_parts.is_empty() {
+            directive.name = Some(name_parts.join(" "));
+        }
+        directive
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ParsedRequest {
     pub name: Option<String>,
@@ -81,6 +298,18 @@ pub struct ParsedRequest {
     pub body: Option<String>,
     pub response_handler: Option<String>,
     pub line_number: usize,
+    /// Line of the first response handler statement (0 when there is no handler).
+    pub handler_line_number: usize,
+    /// JSON Schema files (from `# @schema` directives) the response body must match.
+    pub schemas: Vec<String>,
+    /// Snapshot to compare the response body with (from `# @snapshot`).
+    pub snapshot: Option<SnapshotDirective>,
+    /// Tags for selecting requests with `--tags` (from `# @tag`).
+    pub tags: Vec<String>,
+    /// Names of requests that must run before this one (from `# @depends`).
+    pub depends: Vec<String>,
+    /// Declarative `??` assertions on the response.
+    pub assertions: Vec<Assertion>,
 }
 
 #[derive(Debug)]
@@ -94,59 +323,96 @@ enum ParserState {
 pub struct ParseResult {
     pub requests: Vec<ParsedRequest>,
     pub in_place_vars: Vec<(String, String)>,
+    /// The file contains `# @parallel`: its requests may run concurrently.
+    pub parallel: bool,
+}
+
+/// The request being read, with the directives that came before it.
+#[derive(Debug, Default)]
+struct PendingRequest {
+    name: Option<String>,
+    method: Option<HttpMethod>,
+    url: Option<String>,
+    headers: Vec<Header>,
+    body_lines: Vec<String>,
+    handler_lines: Vec<String>,
+    line_number: usize,
+    handler_line_number: usize,
+    schemas: Vec<String>,
+    snapshot: Option<SnapshotDirective>,
+    tags: Vec<String>,
+    depends: Vec<String>,
+    assertions: Vec<Assertion>,
+}
+
+impl PendingRequest {
+    /// The finished request, or `None` if no request line has been read.
+    fn finish(self) -> Option<ParsedRequest> {
+        let (method, url) = (self.method?, self.url?);
+
+        let body_text = self.body_lines.join("\n");
+        let body = if body_text.trim().is_empty() {
+            None
+        } else {
+            Some(body_text.trim_end().to_string())
+        };
+
+        let handler_text = self.handler_lines.join("\n");
+        let handler = if handler_text.trim().is_empty() {
+            None
+        } else {
+            Some(handler_text)
+        };
+        let handler_line_number = if handler.is_some() { self.handler_line_number } else { 0 };
+
+        Some(ParsedRequest {
+            name: self.name,
+            method,
+            url,
+            headers: self.headers,
+            body,
+            response_handler: handler,
+            line_number: self.line_number,
+            handler_line_number,
+            schemas: self.schemas,
+            snapshot: self.snapshot,
+            tags: self.tags,
+            depends: self.depends,
+            assertions: self.assertions,
+        })
+    }
+}
+
+/// Parse the `??` lines held back at the end of a body into the request's
+/// assertions. If any of them isn't an assertion, they are all body text.
+fn add_assertions(lines: &[&str], trailing: &mut Vec<usize>, pending: &mut PendingRequest) {
+    let parsed: Result<Vec<_>, _> = trailing
+        .iter()
+        .filter_map(|&i| ASSERTION_LINE_RE.captures(lines[i].trim()).map(|caps| (i, caps)))
+        .map(|(i, caps)| Assertion::parse(caps[1].trim(), i + 1))
+        .collect();
+    match parsed {
+        Ok(assertions) => {
+            trailing.clear();
+            pending.assertions.extend(assertions);
+        }
+        Err(_) => pending.body_lines.extend(trailing.drain(..).map(|i| lines[i].to_string())),
+    }
 }
 
 pub fn parse_http_file(content: &str) -> Result<ParseResult, AppError> {
     let lines: Vec<&str> = content.lines().collect();
     let mut requests: Vec<ParsedRequest> = Vec::new();
     let mut in_place_vars: Vec<(String, String)> = Vec::new();
+    let mut parallel = false;
     let mut state = ParserState::AwaitingRequest;
 
-    let mut current_name: Option<String> = None;
-    let mut current_method: Option<HttpMethod> = None;
-    let mut current_url: Option<String> = None;
-    let mut current_headers: Vec<Header> = Vec::new();
-    let mut current_body_lines: Vec<String> = Vec::new();
-    let mut current_handler_lines: Vec<String> = Vec::new();
-    let mut current_line_number: usize = 0;
-
-    let finalize_request =
-        |requests: &mut Vec<ParsedRequest>,
-         name: &mut Option<String>,
-         method: &mut Option<HttpMethod>,
-         url: &mut Option<String>,
-         headers: &mut Vec<Header>,
-         body_lines: &mut Vec<String>,
-         handler_lines: &mut Vec<String>,
-         line_number: usize| {
-            if let (Some(m), Some(u)) = (method.take(), url.take()) {
-                let body_text = body_lines.join("\n");
-                let body = if body_text.trim().is_empty() {
-                    None
-                } else {
-                    Some(body_text.trim_end().to_string())
-                };
-
-                let handler_text = handler_lines.join("\n");
-                let handler = if handler_text.trim().is_empty() {
-                    None
-                } else {
-                    Some(handler_text)
-                };
-
-                requests.push(ParsedRequest {
-                    name: name.take(),
-                    method: m,
-                    url: u,
-                    headers: std::mem::take(headers),
-                    body,
-                    response_handler: handler,
-                    line_number,
-                });
-            }
-            body_lines.clear();
-            handler_lines.clear();
-        };
+    let mut pending = PendingRequest::default();
+    // `??` lines right after a handler belong to the request it finished
+    let mut assertions_follow_handler = false;
+    // Lines held back from the body: `??` lines (and blank lines after them)
+    // that are assertions if nothing but the end of the request follows
+    let mut trailing: Vec<usize> = Vec::new();
 
     for (i, line) in lines.iter().enumerate() {
         let line_num = i + 1;
@@ -156,11 +422,66 @@ pub fn parse_http_file(content: &str) -> Result<ParseResult, AppError> {
             ParserState::AwaitingRequest => {
                 // Skip empty lines and comments
                 if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with('#') {
+                    // `# @schema path` applies to the next request
+                    if let Some(caps) = SCHEMA_DIRECTIVE_RE.captures(trimmed) {
+                        pending.schemas.push(caps[1].to_string());
+                        continue;
+                    }
+                    if let Some(caps) = SNAPSHOT_DIRECTIVE_RE.captures(trimmed) {
+                        let args = caps.get(1).map_or("", |m| m.as_str());
+                        pending.snapshot = Some(SnapshotDirective::parse(args));
+                        continue;
+                    }
+                    // `# @tag smoke, slow` tags the next request
+                    if let Some(caps) = TAG_DIRECTIVE_RE.captures(trimmed) {
+                        pending.tags.extend(
+                            caps[1]
+                                .split(|c: char| c == ',' || c.is_whitespace())
+                                .filter(|t| !t.is_empty())
+                                .map(str::to_string),
+                        );
+                        continue;
+                    }
+                    // `# @depends login, get user` names requests to run first
+                    if let Some(caps) = DEPENDS_DIRECTIVE_RE.captures(trimmed) {
+                        pending.depends.extend(
+                            caps[1]
+                                .split(',')
+                                .map(str::trim)
+                                .filter(|name| !name.is_empty())
+                                .map(str::to_string),
+                        );
+                        continue;
+                    }
+                    // `# @parallel` anywhere in the file opts it into --parallel
+                    if PARALLEL_DIRECTIVE_RE.is_match(trimmed) {
+                        parallel = true;
+                        continue;
+                    }
+
                     // Check for ### separator with optional name
-                    if trimmed.starts_with("###") {
-                        let after = trimmed[3..].trim();
+                    if let Some(rest) = trimmed.strip_prefix("###") {
+                        assertions_follow_handler = false;
+                        let after = rest.trim();
                         if !after.is_empty() {
-                            current_name = Some(after.to_string());
+                            pending.name = Some(after.to_string());
+                        }
+                    }
+                    continue;
+                }
+
+                // `??` assertions after a handler's closing `%}`
+                if let Some(caps) = ASSERTION_LINE_RE.captures(trimmed) {
+                    let assertion = Assertion::parse(caps[1].trim(), line_num)?;
+                    match requests.last_mut() {
+                        Some(request) if assertions_follow_handler => {
+                            request.assertions.push(assertion)
+                        }
+                        _ => {
+                            return Err(AppError::Parse {
+                                line: line_num,
+                                message: "'??' assertion does not belong to a request".to_string(),
+                            });
                         }
                     }
                     continue;
@@ -181,11 +502,12 @@ pub fn parse_http_file(content: &str) -> Result<ParseResult, AppError> {
 
                 // Try to parse as request line
                 if let Some(caps) = REQUEST_LINE_RE.captures(trimmed) {
+                    assertions_follow_handler = false;
                     let method = HttpMethod::from_str(&caps[1]).unwrap();
                     let url = caps[2].to_string();
-                    current_method = Some(method);
-                    current_url = Some(url);
-                    current_line_number = line_num;
+                    pending.method = Some(method);
+                    pending.url = Some(url);
+                    pending.line_number = line_num;
                     state = ParserState::ReadingHeaders;
                 }
             }
@@ -199,25 +521,23 @@ pub fn parse_http_file(content: &str) -> Result<ParseResult, AppError> {
 
                 // Handler start
                 if HANDLER_START_RE.is_match(trimmed) {
+                    pending.handler_line_number = line_num + 1;
                     state = ParserState::ReadingHandler;
                     continue;
                 }
 
+                // Declarative assertion
+                if let Some(caps) = ASSERTION_LINE_RE.captures(trimmed) {
+                    pending.assertions.push(Assertion::parse(caps[1].trim(), line_num)?);
+                    continue;
+                }
+
                 // ### separator means end of this request (no body)
-                if trimmed.starts_with("###") {
-                    finalize_request(
-                        &mut requests,
-                        &mut current_name,
-                        &mut current_method,
-                        &mut current_url,
-                        &mut current_headers,
-                        &mut current_body_lines,
-                        &mut current_handler_lines,
-                        current_line_number,
-                    );
-                    let after = trimmed[3..].trim();
+                if let Some(rest) = trimmed.strip_prefix("###") {
+                    requests.extend(std::mem::take(&mut pending).finish());
+                    let after = rest.trim();
                     if !after.is_empty() {
-                        current_name = Some(after.to_string());
+                        pending.name = Some(after.to_string());
                     }
                     state = ParserState::AwaitingRequest;
                     continue;
@@ -225,23 +545,14 @@ pub fn parse_http_file(content: &str) -> Result<ParseResult, AppError> {
 
                 // Response history
                 if RESPONSE_HISTORY_RE.is_match(trimmed) {
-                    finalize_request(
-                        &mut requests,
-                        &mut current_name,
-                        &mut current_method,
-                        &mut current_url,
-                        &mut current_headers,
-                        &mut current_body_lines,
-                        &mut current_handler_lines,
-                        current_line_number,
-                    );
+                    requests.extend(std::mem::take(&mut pending).finish());
                     state = ParserState::AwaitingRequest;
                     continue;
                 }
 
                 // Try to parse header
                 if let Some(caps) = HEADER_LINE_RE.captures(trimmed) {
-                    current_headers.push(Header {
+                    pending.headers.push(Header {
                         name: caps[1].to_string(),
                         value: caps[2].trim().to_string(),
                     });
@@ -251,25 +562,25 @@ pub fn parse_http_file(content: &str) -> Result<ParseResult, AppError> {
             ParserState::ReadingBody => {
                 // Handler start
                 if HANDLER_START_RE.is_match(trimmed) {
+                    add_assertions(&lines, &mut trailing, &mut pending);
+                    pending.handler_line_number = line_num + 1;
                     state = ParserState::ReadingHandler;
                     continue;
                 }
 
+                // Declarative assertions come after the body
+                if ASSERTION_LINE_RE.is_match(trimmed) || (trimmed.is_empty() && !trailing.is_empty()) {
+                    trailing.push(i);
+                    continue;
+                }
+
                 // ### separator
-                if trimmed.starts_with("###") {
-                    finalize_request(
-                        &mut requests,
-                        &mut current_name,
-                        &mut current_method,
-                        &mut current_url,
-                        &mut current_headers,
-                        &mut current_body_lines,
-                        &mut current_handler_lines,
-                        current_line_number,
-                    );
-                    let after = trimmed[3..].trim();
+                if let Some(rest) = trimmed.strip_prefix("###") {
+                    add_assertions(&lines, &mut trailing, &mut pending);
+                    requests.extend(std::mem::take(&mut pending).finish());
+                    let after = rest.trim();
                     if !after.is_empty() {
-                        current_name = Some(after.to_string());
+                        pending.name = Some(after.to_string());
                     }
                     state = ParserState::AwaitingRequest;
                     continue;
@@ -277,21 +588,15 @@ pub fn parse_http_file(content: &str) -> Result<ParseResult, AppError> {
 
                 // Response history line — finalize current request
                 if RESPONSE_HISTORY_RE.is_match(trimmed) {
-                    finalize_request(
-                        &mut requests,
-                        &mut current_name,
-                        &mut current_method,
-                        &mut current_url,
-                        &mut current_headers,
-                        &mut current_body_lines,
-                        &mut current_handler_lines,
-                        current_line_number,
-                    );
+                    add_assertions(&lines, &mut trailing, &mut pending);
+                    requests.extend(std::mem::take(&mut pending).finish());
                     state = ParserState::AwaitingRequest;
                     continue;
                 }
 
-                current_body_lines.push(line.to_string());
+                // More body follows, so the held-back lines were body text
+                pending.body_lines.extend(trailing.drain(..).map(|i| lines[i].to_string()));
+                pending.body_lines.push(line.to_string());
             }
 
             ParserState::ReadingHandler => {
@@ -299,46 +604,32 @@ pub fn parse_http_file(content: &str) -> Result<ParseResult, AppError> {
                     // End of handler — check what comes next
                     // Stay in a transitional state: finalize when we see ### or <> or next request
                     // For simplicity, finalize now and go to AwaitingRequest
-                    finalize_request(
-                        &mut requests,
-                        &mut current_name,
-                        &mut current_method,
-                        &mut current_url,
-                        &mut current_headers,
-                        &mut current_body_lines,
-                        &mut current_handler_lines,
-                        current_line_number,
-                    );
+                    requests.extend(std::mem::take(&mut pending).finish());
+                    assertions_follow_handler = true;
                     state = ParserState::AwaitingRequest;
                     continue;
                 }
 
-                current_handler_lines.push(line.to_string());
+                pending.handler_lines.push(line.to_string());
             }
         }
     }
 
     // Finalize any remaining request
-    finalize_request(
-        &mut requests,
-        &mut current_name,
-        &mut current_method,
-        &mut current_url,
-        &mut current_headers,
-        &mut current_body_lines,
-        &mut current_handler_lines,
-        current_line_number,
-    );
+    add_assertions(&lines, &mut trailing, &mut pending);
+    requests.extend(std::mem::take(&mut pending).finish());
 
     Ok(ParseResult {
         requests,
         in_place_vars,
+        parallel,
     })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_http_file, HttpMethod};
+    use super::{AssertionOp, AssertionSubject, HttpMethod, SnapshotDirective, parse_http_file};
+    use crate::error::AppError;
 
     #[test]
     fn parses_request_with_body_and_handler() {
@@ -378,8 +669,177 @@ X-Trace: 123
             req.body.as_deref(),
             Some("{\n  \"name\": \"widget\"\n}")
         );
+        assert_eq!(req.handler_line_number, 14);
         let handler = req.response_handler.as_deref().expect("handler present");
         assert!(handler.contains("client.test(\"status is 200\""));
         assert!(handler.contains("client.assert(response.status === 200);"));
     }
+
+    #[test]
+    fn collects_schema_directives_for_next_request() {
+        let content = r#"
+### first
+# @schema ./schemas/user.json
+// @schema ./schemas/audit.json
+GET https://example.com/users/1
+
+### second
+GET https://example.com/health
+"#;
+
+        let parsed = parse_http_file(content).expect("parse should succeed");
+        assert_eq!(
+            parsed.requests[0].schemas,
+            vec!["./schemas/user.json", "./schemas/audit.json"]
+        );
+        assert!(parsed.requests[1].schemas.is_empty());
+    }
+
+    #[test]
+    fn collects_tags_and_dependencies_for_next_request() {
+        let content = r#"
+### smoke check
+# @tag smoke
+// @tags api, slow
+GET https://example.com/health
+
+### untagged
+# @depends smoke check, get user
+GET https://example.com/users
+"#;
+
+        let parsed = parse_http_file(content).expect("parse should succeed");
+        assert_eq!(parsed.requests[0].tags, vec!["smoke", "api", "slow"]);
+        assert!(parsed.requests[0].depends.is_empty());
+        assert!(parsed.requests[1].tags.is_empty());
+        assert_eq!(parsed.requests[1].depends, vec!["smoke check", "get user"]);
+    }
+
+    #[test]
+    fn parses_snapshot_directive() {
+        let content = r#"
+### list users
+# @snapshot users page ignore=$.createdAt,$.items[*].id
+GET https://example.com/users
+
+### plain
+# @snapshot
+GET https://example.com/health
+"#;
+
+        let parsed = parse_http_file(content).expect("parse should succeed");
+        assert_eq!(
+            parsed.requests[0].snapshot,
+            Some(SnapshotDirective {
+                name: Some("users page".to_string()),
+                ignore: vec!["$.createdAt".to_string(), "$.items[*].id".to_string()],
+            })
+        );
+        assert_eq!(parsed.requests[1].snapshot, Some(SnapshotDirective::default()));
+    }
+
+    #[test]
+    fn parses_assertions_around_handler() {
+        let content = r#"
+### get item
+GET https://example.com/items/1
+?? status == 200
+?? jsonpath $[?@.name == 'a b'] length >= 1
+
+> {%
+  client.log("hi");
+%}
+?? header Content-Type contains "application/json"
+
+### next
+GET https://example.com/health
+"#;
+
+        let parsed = parse_http_file(content).expect("parse should succeed");
+        let assertions = &parsed.requests[0].assertions;
+        assert_eq!(assertions.len(), 3);
+        assert_eq!(assertions[0].subject, AssertionSubject::Status);
+        assert_eq!(assertions[0].expected.as_deref(), Some("200"));
+        assert_eq!(
+            assertions[1].subject,
+            AssertionSubject::JsonPath("$[?@.name == 'a b']".to_string())
+        );
+        assert!(assertions[1].length);
+        assert_eq!(assertions[1].op, AssertionOp::Ge);
+        assert_eq!(assertions[2].subject, AssertionSubject::Header("Content-Type".to_string()));
+        assert_eq!(assertions[2].expected.as_deref(), Some("application/json"));
+        assert!(parsed.requests[0].response_handler.is_some());
+        assert!(parsed.requests[1].assertions.is_empty());
+    }
+
+    #[test]
+    fn keeps_body_lines_starting_with_assertion_marker() {
+        let content = r#"### ask
+POST https://example.com/questions
+Content-Type: text/plain
+
+?? what status == 200 means
+is still part of the question
+
+?? status == 201
+
+> {%
+  client.log("asked");
+%}
+
+### answer
+POST https://example.com/answers
+
+?? status ~ is not an assertion
+done
+"#;
+
+        let parsed = parse_http_file(content).expect("parse should succeed");
+        let ask = &parsed.requests[0];
+        assert_eq!(
+            ask.body.as_deref(),
+            Some("?? what status == 200 means\nis still part of the question")
+        );
+        assert_eq!(ask.assertions.len(), 1);
+        assert_eq!(ask.assertions[0].expected.as_deref(), Some("201"));
+        assert!(ask.response_handler.is_some());
+        let answer = &parsed.requests[1];
+        assert_eq!(answer.body.as_deref(), Some("?? status ~ is not an assertion\ndone"));
+        assert!(answer.assertions.is_empty());
+    }
+
+    #[test]
+    fn keeps_trailing_body_lines_that_are_not_assertions() {
+        let content = r#"### ask
+POST https://example.com/questions
+Content-Type: text/plain
+
+Please explain
+?? what status == 200 means
+
+### check
+POST https://example.com/checks
+
+ping
+?? status == 200
+?? header X-Id exists
+"#;
+
+        let parsed = parse_http_file(content).expect("parse should succeed");
+        let ask = &parsed.requests[0];
+        assert_eq!(ask.body.as_deref(), Some("Please explain\n?? what status == 200 means"));
+        assert!(ask.assertions.is_empty());
+        let check = &parsed.requests[1];
+        assert_eq!(check.body.as_deref(), Some("ping"));
+        assert_eq!(check.assertions.len(), 2);
+        assert_eq!(check.assertions[1].line, 13);
+    }
+
+    #[test]
+    fn rejects_malformed_assertions() {
+        let err = parse_http_file("GET https://example.com\n?? status ~ 200\n").err().unwrap();
+        assert!(matches!(err, AppError::Parse { line: 2, .. }), "{err}");
+        assert!(parse_http_file("GET https://example.com\n?? jsonpath $.id exists now\n").is_err());
+        assert!(parse_http_file("?? status == 200\n").is_err());
+    }
 }
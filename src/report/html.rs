@@ -0,0 +1,267 @@
+use std::fmt::Write;
+
+use crate::js::LogLevel;
+use crate::parser::Header;
+use crate::report::{RequestReport, RunReport, SuiteReport, escape};
+
+const STYLE: &str = r#"
+body { font: 14px/1.45 -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 0; padding: 24px 32px; color: #1f2328; background: #f6f8fa; }
+h1 { font-size: 22px; margin: 0 0 16px; }
+h2 { font-size: 16px; margin: 28px 0 8px; font-family: ui-monospace, Menlo, Consolas, monospace; }
+h3 { font-size: 13px; margin: 14px 0 6px; text-transform: uppercase; letter-spacing: .04em; color: #59636e; }
+.cards { display: flex; gap: 12px; flex-wrap: wrap; }
+.card { background: #fff; border: 1px solid #d1d9e0; border-radius: 6px; padding: 10px 16px; min-width: 90px; }
+.card b { display: block; font-size: 22px; }
+.card.ok b { color: #1a7f37; } .card.bad b { color: #cf222e; }
+details { background: #fff; border: 1px solid #d1d9e0; border-left: 4px solid #1a7f37; border-radius: 6px; margin: 6px 0; }
+details.fail { border-left-color: #cf222e; } details.skip { border-left-color: #8c959f; }
+summary { cursor: pointer; padding: 8px 12px; display: flex; align-items: center; gap: 10px; }
+.body { padding: 0 16px 12px; border-top: 1px solid #eef1f4; }
+.badge { font-size: 11px; font-weight: 600; padding: 1px 7px; border-radius: 10px; color: #fff; background: #1a7f37; }
+.badge.fail { background: #cf222e; } .badge.skip { background: #8c959f; }
+.method { font-family: ui-monospace, Menlo, Consolas, monospace; font-weight: 600; }
+.name { flex: 1; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+.url { color: #59636e; font-family: ui-monospace, Menlo, Consolas, monospace; }
+.timing { display: flex; align-items: center; gap: 6px; width: 220px; color: #59636e; font-size: 12px; }
+.bar { flex: 1; height: 8px; background: #eef1f4; border-radius: 4px; overflow: hidden; }
+.bar span { display: block; height: 100%; background: #0969da; }
+table { border-collapse: collapse; font-family: ui-monospace, Menlo, Consolas, monospace; font-size: 12px; }
+td { padding: 2px 12px 2px 0; vertical-align: top; } td:first-child { color: #59636e; white-space: nowrap; }
+pre { background: #f6f8fa; border: 1px solid #eef1f4; border-radius: 4px; padding: 8px; overflow: auto; max-height: 480px; font-size: 12px; margin: 4px 0; }
+ul { list-style: none; padding: 0; margin: 0; } li { margin: 3px 0; }
+li pre { margin-left: 48px; }
+.error { color: #cf222e; }
+.log-warn { color: #9a6700; } .log-error { color: #cf222e; } .log-debug { color: #8c959f; }
+"#;
+
+/// Render the run as a single self-contained HTML page: summary totals, then
+/// one collapsible section per request with its headers, pretty-printed
+/// bodies, test results and a bar comparing its time to the slowest request.
+/// Failed requests start expanded.
+pub fn render(run: &RunReport) -> String {
+    let summary = run.summary();
+    let elapsed: u128 = run.suites.iter().map(SuiteReport::elapsed_ms).sum();
+    let slowest = run
+        .suites
+        .iter()
+        .flat_map(|s| &s.requests)
+        .map(RequestReport::elapsed_ms)
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>httprun report</title>\n");
+    let _ = writeln!(out, "<style>{STYLE}</style>\n</head>\n<body>");
+    let _ = writeln!(
+        out,
+        "<h1>httprun report <span class=\"badge{}\">{}</span></h1>",
+        if summary.success() { "" } else { " fail" },
+        if summary.success() { "PASSED" } else { "FAILED" }
+    );
+
+    out.push_str("<div class=\"cards\">\n");
+    card(&mut out, "Requests", summary.requests, "");
+    card(&mut out, "Passed", summary.passed, "ok");
+    card(&mut out, "Failed", summary.failed, if summary.failed > 0 { "bad" } else { "" });
+    card(&mut out, "Errors", summary.errors, if summary.errors > 0 { "bad" } else { "" });
+    let _ = writeln!(out, "<div class=\"card\"><b>{elapsed} ms</b>Total time</div>");
+    out.push_str("</div>\n");
+
+    for suite in &run.suites {
+        let _ = writeln!(out, "<h2>{}</h2>", escape(&suite.name()));
+        for request in &suite.requests {
+            render_request(&mut out, request, slowest);
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn card(out: &mut String, label: &str, value: usize, class: &str) {
+    let _ = writeln!(out, "<div class=\"card {class}\"><b>{value}</b>{label}</div>");
+}
+
+fn render_request(out: &mut String, request: &RequestReport, slowest: u128) {
+    let failed = !request.errors.is_empty() || request.tests.iter().any(|t| !t.passed);
+    let (class, label) = if request.skipped {
+        ("skip", "SKIP")
+    } else if failed {
+        ("fail", "FAIL")
+    } else {
+        ("pass", "PASS")
+    };
+
+    let _ = writeln!(
+        out,
+        "<details class=\"{class}\"{}>",
+        if failed { " open" } else { "" }
+    );
+    let _ = writeln!(
+        out,
+        "<summary><span class=\"badge {class}\">{label}</span><span class=\"method\">{}</span><span class=\"name\">{} <span class=\"url\">{}</span></span>",
+        escape(&request.method),
+        escape(&request.name),
+        escape(&request.url)
+    );
+    if let Some(response) = &request.response {
+        let width = if slowest == 0 {
+            0.0
+        } else {
+            (response.elapsed_ms as f64 / slowest as f64 * 100.0).max(1.0)
+        };
+        let _ = writeln!(
+            out,
+            "<span class=\"timing\"><span class=\"bar\"><span style=\"width: {width:.1}%\"></span></span>{} ms</span>",
+            response.elapsed_ms
+        );
+    }
+    out.push_str("</summary>\n<div class=\"body\">\n");
+
+    if !request.tests.is_empty() {
+        out.push_str("<h3>Tests</h3>\n<ul>\n");
+        for test in &request.tests {
+            let _ = write!(
+                out,
+                "<li><span class=\"badge{}\">{}</span> {} <span class=\"url\">{} ms</span>",
+                if test.passed { "" } else { " fail" },
+                if test.passed { "PASS" } else { "FAIL" },
+                escape(&test.name),
+                test.duration.as_millis()
+            );
+            if let Some(message) = test.failure_message() {
+                let _ = write!(out, "<pre class=\"error\">{}</pre>", escape(&message));
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+
+    if !request.errors.is_empty() {
+        out.push_str("<h3>Errors</h3>\n");
+        for error in &request.errors {
+            let _ = writeln!(out, "<pre class=\"error\">{}</pre>", escape(&error.message));
+        }
+    }
+
+    if !request.logs.is_empty() {
+        out.push_str("<h3>Logs</h3>\n<pre>");
+        for log in &request.logs {
+            let class = match log.level {
+                LogLevel::Log | LogLevel::Info => "log",
+                LogLevel::Warn => "log-warn",
+                LogLevel::Error => "log-error",
+                LogLevel::Debug => "log-debug",
+            };
+            let _ = writeln!(out, "<span class=\"{class}\">{}</span>", escape(&log.message));
+        }
+        out.push_str("</pre>\n");
+    }
+
+    out.push_str("<h3>Request</h3>\n");
+    let _ = writeln!(
+        out,
+        "<div class=\"method\">{} {}</div>",
+        escape(&request.method),
+        escape(&request.url)
+    );
+    let headers: Vec<(&str, &str)> = request
+        .headers
+        .iter()
+        .map(|Header { name, value }| (name.as_str(), value.as_str()))
+        .collect();
+    render_headers(out, &headers);
+    if let Some(body) = &request.body {
+        let pretty = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| serde_json::to_string_pretty(&v).ok());
+        let _ = writeln!(out, "<pre>{}</pre>", escape(pretty.as_deref().unwrap_or(body)));
+    }
+
+    if let Some(response) = &request.response {
+        let _ = writeln!(out, "<h3>Response</h3>\n<div class=\"method\">{}</div>", response.status);
+        let mut names: Vec<&String> = response.headers.keys().collect();
+        names.sort();
+        let headers: Vec<(&str, &str)> = names
+            .into_iter()
+            .flat_map(|name| response.headers[name].iter().map(|v| (name.as_str(), v.as_str())))
+            .collect();
+        render_headers(out, &headers);
+        let pretty = response
+            .body_json
+            .as_ref()
+            .and_then(|v| serde_json::to_string_pretty(v).ok());
+        let body = pretty.as_deref().unwrap_or(&response.body_raw);
+        if !body.is_empty() {
+            let _ = writeln!(out, "<pre>{}</pre>", escape(body));
+        }
+    }
+
+    out.push_str("</div>\n</details>\n");
+}
+
+fn render_headers(out: &mut String, headers: &[(&str, &str)]) {
+    if headers.is_empty() {
+        return;
+    }
+    out.push_str("<table>\n");
+    for (name, value) in headers {
+        let _ = writeln!(out, "<tr><td>{}</td><td>{}</td></tr>", escape(name), escape(value));
+    }
+    out.push_str("</table>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use super::*;
+    use crate::http::HttpResponse;
+    use crate::js::TestResult;
+    use crate::parser::parse_http_file;
+
+    #[test]
+    fn renders_summary_sections_and_timing_bars() {
+        let parsed = parse_http_file(
+            "### Create\nPOST https://example.com/items\nContent-Type: application/json\n\n{\"a\":1}\n\n### List\nGET https://example.com/items\n",
+        )
+        .unwrap();
+        let mut create = RequestReport::new(1, &parsed.requests[0]);
+        create.response = Some(HttpResponse {
+            status: 201,
+            headers: HashMap::from([("x-id".to_string(), vec!["7".to_string()])]),
+            body_raw: "{\"id\":7}".to_string(),
+            body_json: Some(serde_json::json!({ "id": 7 })),
+            content_type: None,
+            elapsed_ms: 200,
+        });
+        create.tests = vec![TestResult::pass("created")];
+        let mut list = RequestReport::new(2, &parsed.requests[1]);
+        list.response = Some(HttpResponse {
+            status: 200,
+            headers: HashMap::new(),
+            body_raw: "<ok>".to_string(),
+            body_json: None,
+            content_type: None,
+            elapsed_ms: 50,
+        });
+        list.tests = vec![TestResult::fail("has items", "expected 1 item")];
+
+        let mut suite = SuiteReport::new(Path::new("items.http"));
+        suite.requests = vec![create, list];
+        let html = render(&RunReport { suites: vec![suite] });
+
+        assert!(html.contains("<b>2</b>Requests"), "{html}");
+        assert!(html.contains("<span class=\"badge fail\">FAILED</span>"), "{html}");
+        assert!(html.contains("<details class=\"pass\">"), "{html}");
+        assert!(html.contains("<details class=\"fail\" open>"), "{html}");
+        assert!(html.contains("<pre>{\n  &quot;a&quot;: 1\n}</pre>"), "{html}");
+        assert!(html.contains("<pre>{\n  &quot;id&quot;: 7\n}</pre>"), "{html}");
+        assert!(html.contains("<tr><td>x-id</td><td>7</td></tr>"), "{html}");
+        assert!(html.contains("<pre>&lt;ok&gt;</pre>"), "{html}");
+        assert!(html.contains("width: 100.0%"), "{html}");
+        assert!(html.contains("width: 25.0%"), "{html}");
+        assert!(html.contains("<pre class=\"error\">expected 1 item</pre>"), "{html}");
+    }
+}
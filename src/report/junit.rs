@@ -0,0 +1,157 @@
+use std::fmt::Write;
+
+use crate::report::{RequestReport, RunReport, SuiteReport, escape, first_line};
+
+/// Render the run as JUnit XML: one `<testsuite>` per `.http` file and one
+/// `<testcase>` per test result. Request errors become `<error>` test cases
+/// and skipped requests `<skipped>` ones; handler logs go in `<system-out>`.
+pub fn render(run: &RunReport) -> String {
+    let tests: usize = run.suites.iter().map(suite_case_count).sum();
+    let failures: usize = run.suites.iter().map(SuiteReport::failure_count).sum();
+    let errors: usize = run.suites.iter().map(SuiteReport::error_count).sum();
+    let elapsed: u128 = run.suites.iter().map(SuiteReport::elapsed_ms).sum();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites name=\"httprun\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" time=\"{}\">",
+        seconds(elapsed)
+    );
+    for suite in &run.suites {
+        render_suite(&mut out, suite);
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+/// Test cases in a suite: every test result, error and skipped request.
+fn suite_case_count(suite: &SuiteReport) -> usize {
+    suite.test_count() + suite.error_count() + suite.skipped_count()
+}
+
+fn render_suite(out: &mut String, suite: &SuiteReport) {
+    let _ = writeln!(
+        out,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\">",
+        escape(&suite.name()),
+        suite_case_count(suite),
+        suite.failure_count(),
+        suite.error_count(),
+        suite.skipped_count(),
+        seconds(suite.elapsed_ms())
+    );
+
+    for request in &suite.requests {
+        if request.skipped {
+            open_case(out, request, &request.name);
+            out.push_str("      <skipped/>\n");
+            out.push_str("    </testcase>\n");
+            continue;
+        }
+
+        for test in &request.tests {
+            open_case(out, request, &test.name);
+            if let Some(message) = test.failure_message() {
+                let _ = writeln!(
+                    out,
+                    "      <failure message=\"{}\">{}</failure>",
+                    escape(first_line(&message)),
+                    escape(&message)
+                );
+            }
+            render_system_out(out, request);
+            out.push_str("    </testcase>\n");
+        }
+
+        for error in &request.errors {
+            open_case(out, request, &request.name);
+            let _ = writeln!(
+                out,
+                "      <error message=\"{}\">{}</error>",
+                escape(first_line(&error.message)),
+                escape(&error.message)
+            );
+            render_system_out(out, request);
+            out.push_str("    </testcase>\n");
+        }
+    }
+
+    out.push_str("  </testsuite>\n");
+}
+
+/// Test cases are grouped by request; their time is the request's round trip.
+fn open_case(out: &mut String, request: &RequestReport, name: &str) {
+    let _ = writeln!(
+        out,
+        "    <testcase classname=\"{}\" name=\"{}\" time=\"{}\">",
+        escape(&request.name),
+        escape(name),
+        seconds(request.elapsed_ms())
+    );
+}
+
+fn render_system_out(out: &mut String, request: &RequestReport) {
+    if request.logs.is_empty() {
+        return;
+    }
+    let text: Vec<&str> = request.logs.iter().map(|l| l.message.as_str()).collect();
+    let _ = writeln!(out, "      <system-out>{}</system-out>", escape(&text.join("\n")));
+}
+
+fn seconds(ms: u128) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use super::*;
+    use crate::http::HttpResponse;
+    use crate::js::{LogLevel, LogLine, TestResult};
+    use crate::parser::parse_http_file;
+
+    #[test]
+    fn renders_suites_cases_errors_and_logs() {
+        let parsed = parse_http_file(
+            "### Login\nPOST https://example.com/login\n\n### Profile\nGET https://example.com/me\n\n### Later\nGET https://example.com/later\n",
+        )
+        .unwrap();
+        let mut login = RequestReport::new(1, &parsed.requests[0]);
+        login.response = Some(HttpResponse {
+            status: 200,
+            headers: HashMap::new(),
+            body_raw: String::new(),
+            body_json: None,
+            content_type: None,
+            elapsed_ms: 1250,
+        });
+        login.tests = vec![
+            TestResult::pass("token issued"),
+            TestResult::fail("status is 200", "expected <200> & got 500"),
+        ];
+        login.logs = vec![LogLine {
+            level: LogLevel::Log,
+            message: "token=abc".to_string(),
+        }];
+        let mut profile = RequestReport::new(2, &parsed.requests[1]);
+        profile.errors.push("HTTP error: connection refused".to_string().into());
+        let later = RequestReport::skipped(3, &parsed.requests[2]);
+
+        let mut suite = SuiteReport::new(Path::new("api/auth.http"));
+        suite.requests = vec![login, profile, later];
+        let xml = render(&RunReport { suites: vec![suite] });
+
+        assert!(xml.contains(
+            r#"<testsuite name="api/auth.http" tests="4" failures="1" errors="1" skipped="1" time="1.250">"#
+        ), "{xml}");
+        assert!(xml.contains(r#"<testcase classname="Login" name="token issued" time="1.250">"#), "{xml}");
+        assert!(xml.contains(
+            r#"<failure message="expected &lt;200&gt; &amp; got 500">expected &lt;200&gt; &amp; got 500</failure>"#
+        ), "{xml}");
+        assert!(xml.contains("<system-out>token=abc</system-out>"), "{xml}");
+        assert!(xml.contains(r#"<error message="HTTP error: connection refused">"#), "{xml}");
+        assert!(xml.contains("<skipped/>"), "{xml}");
+    }
+}
@@ -0,0 +1,275 @@
+mod html;
+mod junit;
+mod tap;
+mod teamcity;
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::AppError;
+use crate::http::HttpResponse;
+use crate::js::{LogLine, TestResult};
+use crate::load::LoadStats;
+use crate::output::Summary;
+use crate::parser::{Header, ParsedRequest};
+
+/// A report requested with `--report <format>[=<path>]`. Without a path, or
+/// with `-`, the report is written to stdout.
+#[derive(Debug, Clone)]
+pub struct ReportSpec {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl ReportSpec {
+    pub fn to_stdout(&self) -> bool {
+        self.path.as_os_str() == "-"
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Html,
+    Tap,
+    Teamcity,
+}
+
+impl FromStr for ReportSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = s.split_once('=').unwrap_or((s, "-"));
+        let format = match format.trim().to_lowercase().as_str() {
+            "junit" => ReportFormat::Junit,
+            "html" => ReportFormat::Html,
+            "tap" => ReportFormat::Tap,
+            "teamcity" => ReportFormat::Teamcity,
+            other => {
+                return Err(format!(
+                    "unknown report format '{other}' (expected junit, html, tap or teamcity)"
+                ));
+            }
+        };
+        if path.trim().is_empty() {
+            return Err("report path is empty".to_string());
+        }
+        Ok(Self {
+            format,
+            path: PathBuf::from(path.trim()),
+        })
+    }
+}
+
+/// Everything that happened in a run, for writing reports once it ends.
+#[derive(Debug, Default)]
+pub struct RunReport {
+    /// One suite per `.http` file.
+    pub suites: Vec<SuiteReport>,
+}
+
+impl RunReport {
+    /// The same totals the console prints at the end of a run.
+    pub fn summary(&self) -> Summary {
+        let requests = self.suites.iter().flat_map(|s| &s.requests);
+        let executed = requests.clone().filter(|r| !r.skipped).count();
+        let failed = self.suites.iter().map(SuiteReport::failure_count).sum();
+        let tests: usize = self.suites.iter().map(SuiteReport::test_count).sum();
+        Summary {
+            files: self.suites.len(),
+            requests: executed,
+            passed: tests - failed,
+            failed,
+            errors: self.suites.iter().map(SuiteReport::error_count).sum(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SuiteReport {
+    pub file: PathBuf,
+    pub requests: Vec<RequestReport>,
+}
+
+/// The outcome of one request: what was sent, what came back, test results,
+/// handler logs and any errors that stopped it.
+#[derive(Debug, Clone)]
+pub struct RequestReport {
+    /// 1-based position in the `.http` file.
+    pub index: usize,
+    /// Line of the request in the `.http` file.
+    pub line: usize,
+    pub name: String,
+    pub method: String,
+    /// URL, headers and body after variable substitution.
+    pub url: String,
+    pub headers: Vec<Header>,
+    pub body: Option<String>,
+    pub response: Option<HttpResponse>,
+    pub tests: Vec<TestResult>,
+    pub logs: Vec<LogLine>,
+    pub errors: Vec<RequestError>,
+    pub skipped: bool,
+    /// What `--repeat` measured, for requests sent repeatedly.
+    pub load: Option<LoadStats>,
+}
+
+impl RequestReport {
+    pub fn new(index: usize, request: &ParsedRequest) -> Self {
+        Self {
+            index,
+            line: request.line_number,
+            name: request
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Request #{index}")),
+            method: request.method.as_str().to_string(),
+            url: request.url.clone(),
+            headers: request.headers.clone(),
+            body: request.body.clone(),
+            response: None,
+            tests: Vec::new(),
+            logs: Vec::new(),
+            errors: Vec::new(),
+            skipped: false,
+            load: None,
+        }
+    }
+
+    pub fn skipped(index: usize, request: &ParsedRequest) -> Self {
+        Self {
+            skipped: true,
+            ..Self::new(index, request)
+        }
+    }
+
+    /// An error that stopped a whole `.http` file, such as a parse error.
+    /// It is reported as a request of its own so every format shows it.
+    pub fn file_error(file: &Path, error: &AppError) -> Self {
+        Self {
+            index: 0,
+            line: 0,
+            name: file.display().to_string(),
+            method: String::new(),
+            url: String::new(),
+            headers: Vec::new(),
+            body: None,
+            response: None,
+            tests: Vec::new(),
+            logs: Vec::new(),
+            errors: vec![error.to_string().into()],
+            skipped: false,
+            load: None,
+        }
+    }
+
+    /// Round-trip time of the response, or 0 if there was none.
+    pub fn elapsed_ms(&self) -> u128 {
+        self.response.as_ref().map_or(0, |r| r.elapsed_ms)
+    }
+}
+
+/// An error that stopped a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestError {
+    pub message: String,
+    /// Line in the `.http` file the error points at when it is more precise
+    /// than the request's own line, as for a handler exception.
+    pub line: Option<usize>,
+}
+
+impl From<String> for RequestError {
+    fn from(message: String) -> Self {
+        Self { message, line: None }
+    }
+}
+
+impl SuiteReport {
+    pub fn new(file: &Path) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            requests: Vec::new(),
+        }
+    }
+
+    /// Suite name: the `.http` file's path as given on the command line.
+    pub fn name(&self) -> String {
+        self.file.display().to_string()
+    }
+
+    pub fn test_count(&self) -> usize {
+        self.requests.iter().map(|r| r.tests.len()).sum()
+    }
+
+    pub fn failure_count(&self) -> usize {
+        self.requests
+            .iter()
+            .flat_map(|r| &r.tests)
+            .filter(|t| !t.passed)
+            .count()
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.requests.iter().map(|r| r.errors.len()).sum()
+    }
+
+    pub fn skipped_count(&self) -> usize {
+        self.requests.iter().filter(|r| r.skipped).count()
+    }
+
+    pub fn elapsed_ms(&self) -> u128 {
+        self.requests.iter().map(RequestReport::elapsed_ms).sum()
+    }
+}
+
+/// Write every requested report.
+pub fn write_reports(specs: &[ReportSpec], run: &RunReport) -> Result<(), AppError> {
+    for spec in specs {
+        let content = match spec.format {
+            ReportFormat::Junit => junit::render(run),
+            ReportFormat::Html => html::render(run),
+            ReportFormat::Tap => tap::render(run),
+            ReportFormat::Teamcity => teamcity::render(run),
+        };
+        if spec.to_stdout() {
+            print!("{content}");
+            continue;
+        }
+        if let Some(parent) = spec.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&spec.path, content).map_err(|e| {
+            AppError::Io(std::io::Error::new(
+                e.kind(),
+                format!("{}: {}", spec.path.display(), e),
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// The headline of a multi-line failure message.
+pub(crate) fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+/// Escape text for XML or HTML attributes and elements, dropping control
+/// characters XML 1.0 cannot represent.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
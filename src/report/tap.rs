@@ -0,0 +1,150 @@
+use std::fmt::Write;
+
+use crate::report::{RequestReport, RunReport};
+
+const INDENT: &str = "    ";
+
+/// Render the run as TAP version 14: one top-level test point per request and,
+/// for requests with results, a subtest holding one point per test result and
+/// one per request error. Failures carry their message in a YAML block;
+/// durations use the common `# time=` comment.
+pub fn render(run: &RunReport) -> String {
+    let total: usize = run.suites.iter().map(|s| s.requests.len()).sum();
+    let mut out = String::from("TAP version 14\n");
+    let _ = writeln!(out, "1..{total}");
+
+    let mut number = 0;
+    for suite in &run.suites {
+        let _ = writeln!(out, "# {}", single_line(&suite.name()));
+        for request in &suite.requests {
+            number += 1;
+            render_request(&mut out, number, request);
+        }
+    }
+    out
+}
+
+fn render_request(out: &mut String, number: usize, request: &RequestReport) {
+    let name = description(&request.name);
+    if request.skipped {
+        let _ = writeln!(out, "ok {number} - {name} # SKIP");
+        return;
+    }
+
+    let points = request.tests.len() + request.errors.len();
+    if points > 0 {
+        let _ = writeln!(out, "# Subtest: {name}");
+        let _ = writeln!(out, "{INDENT}1..{points}");
+        let mut point = 0;
+        for test in &request.tests {
+            point += 1;
+            let duration = test.duration.as_millis();
+            let _ = writeln!(
+                out,
+                "{INDENT}{} {point} - {} # time={duration}ms",
+                status(test.passed),
+                description(&test.name)
+            );
+            if let Some(message) = test.failure_message() {
+                yaml_block(out, &message, Some(duration));
+            }
+        }
+        for error in &request.errors {
+            point += 1;
+            let _ = writeln!(out, "{INDENT}not ok {point} - error");
+            yaml_block(out, &error.message, None);
+        }
+    }
+
+    let passed = request.errors.is_empty() && request.tests.iter().all(|t| t.passed);
+    let _ = writeln!(
+        out,
+        "{} {number} - {name} # time={}ms",
+        status(passed),
+        request.elapsed_ms()
+    );
+}
+
+fn status(passed: bool) -> &'static str {
+    if passed { "ok" } else { "not ok" }
+}
+
+/// A subtest point's YAML diagnostics, indented under the point.
+fn yaml_block(out: &mut String, message: &str, duration_ms: Option<u128>) {
+    let pad = format!("{INDENT}  ");
+    let _ = writeln!(out, "{pad}---");
+    let _ = writeln!(out, "{pad}message: |-");
+    for line in message.lines() {
+        let _ = writeln!(out, "{pad}  {line}");
+    }
+    if let Some(ms) = duration_ms {
+        let _ = writeln!(out, "{pad}duration_ms: {ms}");
+    }
+    let _ = writeln!(out, "{pad}...");
+}
+
+/// Test point descriptions end at `#` (the directive) and at the line end.
+fn description(name: &str) -> String {
+    single_line(name).replace('\\', "\\\\").replace('#', "\\#")
+}
+
+fn single_line(text: &str) -> String {
+    text.lines().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::js::TestResult;
+    use crate::parser::parse_http_file;
+    use crate::report::SuiteReport;
+
+    #[test]
+    fn renders_requests_as_subtests() {
+        let parsed = parse_http_file(
+            "### Login #1\nPOST https://example.com/login\n\n### Profile\nGET https://example.com/me\n\n### Later\nGET https://example.com/later\n",
+        )
+        .unwrap();
+        let mut login = RequestReport::new(1, &parsed.requests[0]);
+        let mut failed = TestResult::fail("status is 200", "expected 200\nbut got 500");
+        failed.duration = Duration::from_millis(4);
+        login.tests = vec![TestResult::pass("token issued"), failed];
+        let mut profile = RequestReport::new(2, &parsed.requests[1]);
+        profile.errors.push("HTTP error: connection refused".to_string().into());
+        let later = RequestReport::skipped(3, &parsed.requests[2]);
+
+        let mut suite = SuiteReport::new(Path::new("auth.http"));
+        suite.requests = vec![login, profile, later];
+        let tap = render(&RunReport { suites: vec![suite] });
+
+        assert_eq!(
+            tap,
+            "TAP version 14\n\
+             1..3\n\
+             # auth.http\n\
+             # Subtest: Login \\#1\n    \
+             1..2\n    \
+             ok 1 - token issued # time=0ms\n    \
+             not ok 2 - status is 200 # time=4ms\n      \
+             ---\n      \
+             message: |-\n        \
+             expected 200\n        \
+             but got 500\n      \
+             duration_ms: 4\n      \
+             ...\n\
+             not ok 1 - Login \\#1 # time=0ms\n\
+             # Subtest: Profile\n    \
+             1..1\n    \
+             not ok 1 - error\n      \
+             ---\n      \
+             message: |-\n        \
+             HTTP error: connection refused\n      \
+             ...\n\
+             not ok 2 - Profile # time=0ms\n\
+             ok 3 - Later # SKIP\n"
+        );
+    }
+}
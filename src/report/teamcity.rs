@@ -0,0 +1,125 @@
+use std::fmt::Write;
+
+use crate::report::{RequestReport, RunReport, first_line};
+
+/// Render the run as TeamCity service messages: a suite per `.http` file with
+/// a nested suite per request, and a test per test result. Request errors are
+/// reported as a failed test named `error`, skipped requests as ignored tests.
+pub fn render(run: &RunReport) -> String {
+    let mut out = String::new();
+    for suite in &run.suites {
+        let file = suite.name();
+        message(&mut out, "testSuiteStarted", &[("name", &file)]);
+        for request in &suite.requests {
+            render_request(&mut out, request);
+        }
+        message(&mut out, "testSuiteFinished", &[("name", &file)]);
+    }
+    out
+}
+
+fn render_request(out: &mut String, request: &RequestReport) {
+    let suite = &request.name;
+    message(out, "testSuiteStarted", &[("name", suite)]);
+
+    if request.skipped {
+        message(out, "testIgnored", &[("name", suite), ("message", "skipped")]);
+    }
+
+    for test in &request.tests {
+        let name = &test.name;
+        message(out, "testStarted", &[("name", name)]);
+        if let Some(failure) = test.failure_message() {
+            message(
+                out,
+                "testFailed",
+                &[("name", name), ("message", first_line(&failure)), ("details", &failure)],
+            );
+        }
+        let duration = test.duration.as_millis().to_string();
+        message(out, "testFinished", &[("name", name), ("duration", &duration)]);
+    }
+
+    for error in &request.errors {
+        message(out, "testStarted", &[("name", "error")]);
+        message(
+            out,
+            "testFailed",
+            &[("name", "error"), ("message", first_line(&error.message)), ("details", &error.message)],
+        );
+        message(out, "testFinished", &[("name", "error")]);
+    }
+
+    message(out, "testSuiteFinished", &[("name", suite)]);
+}
+
+fn message(out: &mut String, kind: &str, attributes: &[(&str, &str)]) {
+    let _ = write!(out, "##teamcity[{kind}");
+    for (name, value) in attributes {
+        let _ = write!(out, " {name}='{}'", escape(value));
+    }
+    out.push_str("]\n");
+}
+
+/// Service message values escape `|`, quotes, brackets and line breaks with `|`.
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '|' => out.push_str("||"),
+            '\'' => out.push_str("|'"),
+            '[' => out.push_str("|["),
+            ']' => out.push_str("|]"),
+            '\n' => out.push_str("|n"),
+            '\r' => out.push_str("|r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::js::TestResult;
+    use crate::parser::parse_http_file;
+    use crate::report::SuiteReport;
+
+    #[test]
+    fn renders_nested_suites_and_tests() {
+        let parsed = parse_http_file(
+            "### Login\nPOST https://example.com/login\n\n### Later\nGET https://example.com/later\n",
+        )
+        .unwrap();
+        let mut login = RequestReport::new(1, &parsed.requests[0]);
+        let mut failed = TestResult::fail("body['ok']", "expected true\nbut got 'no'");
+        failed.duration = Duration::from_millis(12);
+        login.tests = vec![failed];
+        login.errors.push("Handler error: boom".to_string().into());
+        let later = RequestReport::skipped(2, &parsed.requests[1]);
+
+        let mut suite = SuiteReport::new(Path::new("auth.http"));
+        suite.requests = vec![login, later];
+        let messages = render(&RunReport { suites: vec![suite] });
+
+        assert_eq!(
+            messages,
+            "##teamcity[testSuiteStarted name='auth.http']\n\
+             ##teamcity[testSuiteStarted name='Login']\n\
+             ##teamcity[testStarted name='body|[|'ok|'|]']\n\
+             ##teamcity[testFailed name='body|[|'ok|'|]' message='expected true' details='expected true|nbut got |'no|'']\n\
+             ##teamcity[testFinished name='body|[|'ok|'|]' duration='12']\n\
+             ##teamcity[testStarted name='error']\n\
+             ##teamcity[testFailed name='error' message='Handler error: boom' details='Handler error: boom']\n\
+             ##teamcity[testFinished name='error']\n\
+             ##teamcity[testSuiteFinished name='Login']\n\
+             ##teamcity[testSuiteStarted name='Later']\n\
+             ##teamcity[testIgnored name='Later' message='skipped']\n\
+             ##teamcity[testSuiteFinished name='Later']\n\
+             ##teamcity[testSuiteFinished name='auth.http']\n"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::path::Path;
+
+use jsonschema::{Draft, Retrieve, Uri};
+use serde_json::Value;
+use url::Url;
+
+use crate::error::AppError;
+use crate::js::TestResult;
+
+/// A single place where a JSON document does not match its schema.
+#[derive(Debug, Clone)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value (empty for the document root).
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Loads `$ref`d schema files. jsonschema's own file retriever reads the URI
+/// path as-is, so paths that had to be percent-encoded (spaces, `#`) would
+/// not be found.
+struct FileRetriever;
+
+impl Retrieve for FileRetriever {
+    fn retrieve(&self, uri: &Uri<String>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let path = Url::parse(uri.as_str())?
+            .to_file_path()
+            .map_err(|()| format!("'{uri}' is not a local file"))?;
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+}
+
+/// Validate `instance` against the JSON Schema stored at `schema_path`.
+///
+/// Schemas without a `$schema` keyword are treated as draft 2020-12. Relative
+/// `$ref`s resolve against the schema file, so schemas can be split across
+/// local files.
+pub fn validate_file(instance: &Value, schema_path: &Path) -> Result<Vec<SchemaViolation>, AppError> {
+    let text = std::fs::read_to_string(schema_path).map_err(|e| {
+        AppError::Schema(format!("could not read '{}': {e}", schema_path.display()))
+    })?;
+    let schema: Value = serde_json::from_str(&text).map_err(|e| {
+        AppError::Schema(format!("'{}' is not valid JSON: {e}", schema_path.display()))
+    })?;
+
+    let absolute = schema_path
+        .canonicalize()
+        .or_else(|_| std::path::absolute(schema_path))
+        .unwrap_or_else(|_| schema_path.to_path_buf());
+    let base_uri = Url::from_file_path(&absolute).map_err(|()| {
+        AppError::Schema(format!("'{}' has no file URI", schema_path.display()))
+    })?;
+    let mut options = jsonschema::options()
+        .with_base_uri(base_uri.to_string())
+        .with_retriever(FileRetriever);
+    if schema.get("$schema").is_none() {
+        options = options.with_draft(Draft::Draft202012);
+    }
+    let validator = options.build(&schema).map_err(|e| {
+        AppError::Schema(format!("invalid schema '{}': {e}", schema_path.display()))
+    })?;
+
+    Ok(validator
+        .iter_errors(instance)
+        .map(|e| SchemaViolation {
+            pointer: e.instance_path().to_string(),
+            message: e.to_string(),
+        })
+        .collect())
+}
+
+/// Turn a validation outcome into test results: one failed result per
+/// violation, or a single passing result when the document is valid.
+pub fn to_test_results(
+    label: &str,
+    outcome: Result<Vec<SchemaViolation>, AppError>,
+) -> Vec<TestResult> {
+    let name = format!("Schema {label}");
+    match outcome {
+        Ok(violations) if violations.is_empty() => vec![TestResult::pass(name)],
+        Ok(violations) => violations
+            .into_iter()
+            .map(|v| {
+                let pointer = if v.pointer.is_empty() { "/" } else { v.pointer.as_str() };
+                TestResult::fail(format!("{name} at {pointer}"), v.message)
+            })
+            .collect(),
+        Err(e) => vec![TestResult::fail(name, e.to_string())],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema_dir(name: &str) -> std::path::PathBuf {
+        // Characters that have to be escaped in a file URI
+        let dir = std::env::temp_dir().join(format!("httprun schema #{name}-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("defs")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn reports_violations_with_pointers_across_refs() {
+        let dir = schema_dir("refs");
+        std::fs::write(
+            dir.join("defs/id.json"),
+            r#"{ "type": "integer", "minimum": 1 }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("user.json"),
+            r#"{
+                "type": "object",
+                "required": ["id", "name"],
+                "properties": {
+                    "id": { "$ref": "./defs/id.json" },
+                    "tags": { "type": "array", "items": { "type": "string" } }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let instance = serde_json::json!({ "id": 0, "tags": ["a", 2] });
+        let violations = validate_file(&instance, &dir.join("user.json")).unwrap();
+        let pointers: Vec<&str> = violations.iter().map(|v| v.pointer.as_str()).collect();
+        assert!(pointers.contains(&""), "{violations:?}");
+        assert!(pointers.contains(&"/id"), "{violations:?}");
+        assert!(pointers.contains(&"/tags/1"), "{violations:?}");
+
+        let valid = serde_json::json!({ "id": 3, "name": "x" });
+        assert!(validate_file(&valid, &dir.join("user.json")).unwrap().is_empty());
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn missing_schema_is_a_failed_result() {
+        let results = to_test_results(
+            "missing.json",
+            validate_file(&Value::Null, Path::new("/nonexistent/missing.json")),
+        );
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].passed);
+    }
+}
@@ -0,0 +1,453 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::error::AppError;
+use crate::parser::ParsedRequest;
+use crate::variable::VariableStore;
+
+// `client.global.set("name", ...)` in a response handler
+static GLOBAL_SET_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"client\.global\.set\(\s*["'`]([^"'`]+)["'`]"#).unwrap()
+});
+
+/// A `--index` value: a 1-based index (`3`) or an inclusive range (`2-5`,
+/// open-ended as `4-` or `-2`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexRange {
+    start: usize,
+    end: Option<usize>,
+}
+
+impl IndexRange {
+    fn contains(&self, index: usize) -> bool {
+        index >= self.start && self.end.is_none_or(|end| index <= end)
+    }
+
+    /// Fail if the range reaches past the last of `count` requests.
+    fn check(&self, count: usize) -> Result<(), AppError> {
+        if self.start <= count && self.end.is_none_or(|end| end <= count) {
+            return Ok(());
+        }
+        let what = if self.end == Some(self.start) { "Index" } else { "Range" };
+        Err(AppError::Usage(format!("{what} {self} out of range (1-{count})")))
+    }
+}
+
+impl fmt::Display for IndexRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.end {
+            Some(end) if end == self.start => write!(f, "{end}"),
+            Some(end) => write!(f, "{}-{end}", self.start),
+            None => write!(f, "{}-", self.start),
+        }
+    }
+}
+
+impl FromStr for IndexRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |part: &str| -> Result<usize, String> {
+            match part.trim().parse::<usize>() {
+                Ok(0) => Err("indexes start at 1".to_string()),
+                Ok(n) => Ok(n),
+                Err(_) => Err(format!("expected an index or a range like 2-5, got '{s}'")),
+            }
+        };
+        let range = match s.split_once('-') {
+            None => {
+                let index = parse(s)?;
+                Self { start: index, end: Some(index) }
+            }
+            Some((start, end)) => Self {
+                start: if start.trim().is_empty() { 1 } else { parse(start)? },
+                end: if end.trim().is_empty() { None } else { Some(parse(end)?) },
+            },
+        };
+        if range.end.is_some_and(|end| end < range.start) {
+            return Err(format!("range '{s}' ends before it starts"));
+        }
+        Ok(range)
+    }
+}
+
+/// A `--tags` expression such as `smoke,api,!slow`: a request matches if it
+/// has any of the plain tags (when there are some) and none of the `!` ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl TagFilter {
+    fn matches(&self, tags: &[String]) -> bool {
+        let has = |wanted: &String| tags.iter().any(|t| t.eq_ignore_ascii_case(wanted));
+        (self.include.is_empty() || self.include.iter().any(has)) && !self.exclude.iter().any(has)
+    }
+}
+
+impl FromStr for TagFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+        };
+        for tag in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            match tag.strip_prefix('!') {
+                Some(tag) => filter.exclude.push(tag.trim().to_string()),
+                None => filter.include.push(tag.to_string()),
+            }
+        }
+        if filter.include.is_empty() && filter.exclude.is_empty() {
+            return Err("expected tags like smoke,!slow".to_string());
+        }
+        Ok(filter)
+    }
+}
+
+/// Which requests of a file to run. `--name` patterns and `--index` ranges
+/// each pick requests (any match is enough; with neither, every request is
+/// picked), then every `--tags` expression must match.
+#[derive(Debug, Default)]
+pub struct Selection {
+    names: Vec<Regex>,
+    indexes: Vec<IndexRange>,
+    tags: Vec<TagFilter>,
+}
+
+impl Selection {
+    /// `names` are case-insensitive regular expressions matched anywhere in a
+    /// request's name.
+    pub fn new(names: &[String], indexes: &[IndexRange], tags: &[TagFilter]) -> Result<Self, AppError> {
+        let names = names
+            .iter()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| AppError::Usage(format!("invalid --name pattern '{pattern}': {e}")))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            names,
+            indexes: indexes.to_vec(),
+            tags: tags.to_vec(),
+        })
+    }
+
+    /// Fail if an `--index` value points past the end of a file with `count`
+    /// requests, rather than quietly selecting nothing. Each file is checked
+    /// on its own, so with several files only the shorter ones fail.
+    pub fn check_indexes(&self, count: usize) -> Result<(), AppError> {
+        self.indexes.iter().try_for_each(|range| range.check(count))
+    }
+
+    /// The selected requests with their 0-based positions in the file.
+    pub fn select<'a>(&self, requests: &'a [ParsedRequest]) -> Vec<(usize, &'a ParsedRequest)> {
+        requests
+            .iter()
+            .enumerate()
+            .filter(|(i, request)| self.matches(i + 1, request))
+            .collect()
+    }
+
+    fn matches(&self, index: usize, request: &ParsedRequest) -> bool {
+        let picked = (self.names.is_empty() && self.indexes.is_empty())
+            || request
+                .name
+                .as_ref()
+                .is_some_and(|name| self.names.iter().any(|re| re.is_match(name)))
+            || self.indexes.iter().any(|range| range.contains(index));
+        picked && self.tags.iter().all(|filter| filter.matches(&request.tags))
+    }
+}
+
+/// The requests to run for a selection, each after its dependencies.
+#[derive(Debug)]
+pub struct Plan<'a> {
+    /// The selected requests and their prerequisites, with their 0-based
+    /// positions in the file.
+    pub requests: Vec<(usize, &'a ParsedRequest)>,
+    /// For each of `requests`, the positions in `requests` of the ones it
+    /// directly depends on.
+    pub dependencies: Vec<Vec<usize>>,
+    /// Dependencies that were ignored: a request's `# @depends` on itself, and
+    /// inferred ones that would close a cycle.
+    pub warnings: Vec<String>,
+}
+
+/// Add the requests the selected ones depend on and order them so each runs
+/// after its dependencies, otherwise keeping file order. Dependencies are
+/// declared with `# @depends`; with `infer`, a request also depends on the
+/// request whose handler `client.global.set`s a `{{variable}}` it uses that
+/// `infer` doesn't already define (the closest one before it, if any).
+pub fn with_dependencies<'a>(
+    requests: &'a [ParsedRequest],
+    selected: &[(usize, &ParsedRequest)],
+    infer: Option<&VariableStore>,
+) -> Result<Plan<'a>, AppError> {
+    let mut planner = Planner {
+        requests,
+        infer,
+        marks: vec![Mark::Unvisited; requests.len()],
+        edges: vec![Vec::new(); requests.len()],
+        order: Vec::new(),
+        warnings: Vec::new(),
+    };
+    for &(index, _) in selected {
+        planner.visit(index)?;
+    }
+
+    let order = planner.order;
+    let dependencies = order
+        .iter()
+        .map(|&index| {
+            let mut positions: Vec<usize> = planner.edges[index]
+                .iter()
+                .filter_map(|&dependency| order.iter().position(|&i| i == dependency))
+                .collect();
+            positions.sort_unstable();
+            positions.dedup();
+            positions
+        })
+        .collect();
+    Ok(Plan {
+        requests: order.into_iter().map(|i| (i, &requests[i])).collect(),
+        dependencies,
+        warnings: planner.warnings,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Unvisited,
+    Visiting,
+    Done,
+}
+
+/// A depth-first walk over the dependencies of the selected requests.
+struct Planner<'a, 'v> {
+    requests: &'a [ParsedRequest],
+    infer: Option<&'v VariableStore>,
+    marks: Vec<Mark>,
+    /// The dependencies kept for each request, by position in the file.
+    edges: Vec<Vec<usize>>,
+    order: Vec<usize>,
+    warnings: Vec<String>,
+}
+
+impl Planner<'_, '_> {
+    fn visit(&mut self, index: usize) -> Result<(), AppError> {
+        if self.marks[index] != Mark::Unvisited {
+            return Ok(());
+        }
+        self.marks[index] = Mark::Visiting;
+        for dependency in self.dependencies(index)? {
+            let target = &self.requests[dependency.index];
+            if self.marks[dependency.index] == Mark::Visiting {
+                if !dependency.inferred {
+                    return Err(AppError::Parse {
+                        line: target.line_number,
+                        message: format!(
+                            "dependency cycle through '{}'",
+                            display_name(dependency.index, target)
+                        ),
+                    });
+                }
+                self.warnings.push(format!(
+                    "Ignoring the inferred dependency of '{}' on '{}': it would close a cycle",
+                    display_name(index, &self.requests[index]),
+                    display_name(dependency.index, target)
+                ));
+                continue;
+            }
+            self.visit(dependency.index)?;
+            self.edges[index].push(dependency.index);
+        }
+        self.marks[index] = Mark::Done;
+        self.order.push(index);
+        Ok(())
+    }
+
+    fn dependencies(&mut self, index: usize) -> Result<Vec<Dependency>, AppError> {
+        let requests = self.requests;
+        let request = &requests[index];
+        let mut found = Vec::new();
+        for name in &request.depends {
+            let position = requests
+                .iter()
+                .position(|r| r.name.as_ref().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+                .ok_or_else(|| AppError::Parse {
+                    line: request.line_number,
+                    message: format!("@depends: no request named '{name}'"),
+                })?;
+            if position == index {
+                self.warnings.push(format!(
+                    "Ignoring '# @depends {name}' of '{}': a request can't depend on itself",
+                    display_name(index, request)
+                ));
+                continue;
+            }
+            found.push(Dependency { index: position, inferred: false });
+        }
+
+        if let Some(variables) = self.infer {
+            let mut text = request.url.clone();
+            for header in &request.headers {
+                text.push('\n');
+                text.push_str(&header.value);
+            }
+            if let Some(body) = &request.body {
+                text.push('\n');
+                text.push_str(body);
+            }
+            for variable in variables.undefined_in(&text) {
+                let setters: Vec<usize> = requests
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, r)| i != index && sets_global(r, &variable))
+                    .map(|(i, _)| i)
+                    .collect();
+                let closest = setters.iter().rev().find(|&&i| i < index).or(setters.first());
+                found.extend(closest.map(|&i| Dependency { index: i, inferred: true }));
+            }
+        }
+        Ok(found)
+    }
+}
+
+struct Dependency {
+    /// Position of the request depended on in the file.
+    index: usize,
+    /// Found with `--infer-deps` rather than declared with `# @depends`.
+    inferred: bool,
+}
+
+fn sets_global(request: &ParsedRequest, name: &str) -> bool {
+    request.response_handler.as_deref().is_some_and(|handler| {
+        GLOBAL_SET_RE
+            .captures_iter(handler)
+            .any(|caps| &caps[1] == name)
+    })
+}
+
+fn display_name(index: usize, request: &ParsedRequest) -> String {
+    request
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("Request #{}", index + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_http_file;
+
+    fn selected(selection: &Selection) -> Vec<usize> {
+        let parsed = parse_http_file(
+            "### Login\n# @tag smoke auth\nPOST https://example.com/login\n\n\
+             ### List users\n# @tag smoke\nGET https://example.com/users\n\n\
+             ### Export users\n# @tag slow\nGET https://example.com/export\n\n\
+             ### Health\n# @tag smoke slow\nGET https://example.com/health\n",
+        )
+        .unwrap();
+        selection
+            .select(&parsed.requests)
+            .into_iter()
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+
+    #[test]
+    fn selects_by_name_index_and_tags() {
+        let tags = |s: &str| s.parse::<TagFilter>().unwrap();
+        let index = |s: &str| s.parse::<IndexRange>().unwrap();
+
+        assert_eq!(selected(&Selection::default()), [1, 2, 3, 4]);
+        assert_eq!(selected(&Selection::new(&["USERS$".into()], &[], &[]).unwrap()), [2, 3]);
+        assert_eq!(selected(&Selection::new(&["^login".into(), "health".into()], &[], &[]).unwrap()), [1, 4]);
+        assert_eq!(selected(&Selection::new(&[], &[index("2-3")], &[]).unwrap()), [2, 3]);
+        assert_eq!(selected(&Selection::new(&[], &[index("1"), index("3-")], &[]).unwrap()), [1, 3, 4]);
+        assert_eq!(selected(&Selection::new(&["login".into()], &[index("4")], &[]).unwrap()), [1, 4]);
+        assert_eq!(selected(&Selection::new(&[], &[], &[tags("smoke,!slow")]).unwrap()), [1, 2]);
+        assert_eq!(selected(&Selection::new(&[], &[], &[tags("smoke"), tags("slow")]).unwrap()), [4]);
+        assert_eq!(selected(&Selection::new(&[], &[index("-2")], &[tags("!auth")]).unwrap()), [2]);
+
+        let check = |ranges: &[&str]| {
+            let ranges: Vec<IndexRange> = ranges.iter().map(|s| index(s)).collect();
+            Selection::new(&[], &ranges, &[]).unwrap().check_indexes(4).map_err(|e| e.to_string())
+        };
+        assert!(check(&["4", "2-4", "-4", "4-"]).is_ok());
+        assert!(check(&["99"]).unwrap_err().contains("Index 99 out of range (1-4)"));
+        assert!(check(&["2-5"]).unwrap_err().contains("Range 2-5 out of range (1-4)"));
+        assert!(check(&["5-"]).unwrap_err().contains("Range 5- out of range (1-4)"));
+
+        assert!("0".parse::<IndexRange>().is_err());
+        assert!("5-2".parse::<IndexRange>().is_err());
+        assert!(",".parse::<TagFilter>().is_err());
+        assert!(matches!(Selection::new(&["(".into()], &[], &[]), Err(AppError::Usage(_))));
+    }
+
+    #[test]
+    fn adds_declared_and_inferred_dependencies_first() {
+        let parsed = parse_http_file(
+            "### Get profile\n# @depends login\nGET https://example.com/me\nAuthorization: Bearer {{authToken}}\n\n\
+             ### Login\nPOST https://example.com/login\n\n> {%\nclient.global.set(\"authToken\", response.body.token);\n%}\n\n\
+             ### Orders\nGET https://example.com/orders?user={{userId}}\n\n\
+             ### Me\nGET https://example.com/me\n\n> {%\nclient.global.set('userId', response.body.id);\n%}\n\n\
+             ### Loop\n# @depends loop\nGET https://example.com/loop\n\n\
+             ### A\n# @depends b\nGET https://example.com/a\n\n\
+             ### B\n# @depends a\nGET https://example.com/b\n",
+        )
+        .unwrap();
+        let requests = &parsed.requests;
+        let indexes = |picked: &[usize], infer: Option<&VariableStore>| {
+            let selected: Vec<_> = picked.iter().map(|&i| (i, &requests[i])).collect();
+            with_dependencies(requests, &selected, infer)
+                .map(|plan| plan.requests.into_iter().map(|(i, _)| i).collect::<Vec<_>>())
+        };
+        let variables = VariableStore::new(Default::default());
+
+        assert_eq!(indexes(&[0], None).unwrap(), [1, 0]);
+        assert_eq!(indexes(&[2], None).unwrap(), [2]);
+        assert_eq!(indexes(&[2], Some(&variables)).unwrap(), [3, 2]);
+        assert_eq!(indexes(&[0, 1, 2, 3], Some(&variables)).unwrap(), [1, 0, 3, 2]);
+        assert_eq!(indexes(&[4], None).unwrap(), [4]);
+        assert!(indexes(&[5], None).is_err());
+
+        let plan = with_dependencies(requests, &[(0, &requests[0]), (2, &requests[2])], Some(&variables)).unwrap();
+        assert_eq!(plan.dependencies, [vec![], vec![0], vec![], vec![2]]);
+        assert!(plan.warnings.is_empty());
+    }
+
+    #[test]
+    fn ignores_self_dependencies_and_inferred_cycles_with_a_warning() {
+        let parsed = parse_http_file(
+            "### Loop\n# @depends loop\nGET https://example.com/loop\n\n\
+             ### Create\nPOST https://example.com/items?owner={{ownerId}}\n\n> {%\nclient.global.set(\"itemId\", response.body.id);\n%}\n\n\
+             ### Owner\nGET https://example.com/items/{{itemId}}/owner\n\n> {%\nclient.global.set(\"ownerId\", response.body.id);\n%}\n\n\
+             ### Health\nGET https://example.com/health\n",
+        )
+        .unwrap();
+        let requests = &parsed.requests;
+        let variables = VariableStore::new(Default::default());
+        let all: Vec<_> = requests.iter().enumerate().collect();
+
+        let plan = with_dependencies(requests, &all, Some(&variables)).unwrap();
+        let order: Vec<usize> = plan.requests.iter().map(|&(i, _)| i).collect();
+        assert_eq!(order, [0, 2, 1, 3]);
+        assert_eq!(plan.dependencies, [vec![], vec![], vec![1], vec![]]);
+        assert_eq!(
+            plan.warnings,
+            [
+                "Ignoring '# @depends loop' of 'Loop': a request can't depend on itself",
+                "Ignoring the inferred dependency of 'Owner' on 'Create': it would close a cycle",
+            ]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+use crate::diff::{collapse_unchanged, diff_lines};
+use crate::http::HttpResponse;
+use crate::js::TestResult;
+
+/// Placeholder written in place of values matched by an `ignore` path.
+const IGNORED_PLACEHOLDER: &str = "<ignored>";
+
+/// Unchanged lines shown around each difference in a snapshot diff.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Where snapshots for a `.http` file live and whether to overwrite them.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotSettings {
+    /// `__snapshots__/<file stem>/` next to the `.http` file.
+    pub dir: PathBuf,
+    /// Rewrite existing snapshots instead of comparing (`--update-snapshots`).
+    pub update: bool,
+}
+
+impl SnapshotSettings {
+    pub fn new(http_file: &Path, update: bool) -> Self {
+        let stem = http_file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let dir = http_file
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join("__snapshots__")
+            .join(stem);
+        Self { dir, update }
+    }
+
+    fn path_for(&self, name: &str) -> PathBuf {
+        let file_name: String = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{file_name}.json"))
+    }
+}
+
+/// The value a snapshot records for a response: the JSON body if it has one,
+/// otherwise the raw body text.
+pub fn body_value(response: &HttpResponse) -> Value {
+    response
+        .body_json
+        .clone()
+        .unwrap_or_else(|| Value::String(response.body_raw.clone()))
+}
+
+/// Compare `value` with the stored snapshot `name`, writing it when it does not
+/// exist yet (or when updating). Values matched by the `ignore` JSONPaths are
+/// masked on both sides before comparing.
+pub fn check(settings: &SnapshotSettings, name: &str, value: &Value, ignore: &[String]) -> TestResult {
+    let test_name = format!("Snapshot {name}");
+    let fail = |message: String| TestResult::fail(&test_name, message);
+
+    let current = match mask(value, ignore) {
+        Ok(v) => v,
+        Err(e) => return fail(e),
+    };
+    let current_text = serde_json::to_string_pretty(&current).unwrap_or_default();
+    let path = settings.path_for(name);
+
+    if settings.update || !path.exists() {
+        let written = std::fs::create_dir_all(&settings.dir)
+            .and_then(|_| std::fs::write(&path, format!("{current_text}\n")));
+        return match written {
+            Ok(()) => TestResult::pass(format!("{test_name} (written to {})", path.display())),
+            Err(e) => fail(format!("could not write {}: {e}", path.display())),
+        };
+    }
+
+    let stored = match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()))
+    {
+        Ok(stored) => stored,
+        Err(e) => return fail(format!("could not read {}: {e}", path.display())),
+    };
+    // Masking the stored side too lets `ignore` paths be added after recording
+    let stored = match mask(&stored, ignore) {
+        Ok(v) => v,
+        Err(e) => return fail(e),
+    };
+
+    if stored == current {
+        return TestResult::pass(test_name);
+    }
+
+    let stored_text = serde_json::to_string_pretty(&stored).unwrap_or_default();
+    let mut message = format!(
+        "response does not match {} (run with --update-snapshots to accept)\n  - snapshot\n  + received",
+        path.display()
+    );
+    for line in collapse_unchanged(diff_lines(&stored_text, &current_text), DIFF_CONTEXT_LINES) {
+        message.push('\n');
+        message.push_str(&line);
+    }
+    fail(message)
+}
+
+/// Replace every value matched by the `ignore` JSONPaths with a placeholder.
+fn mask(value: &Value, ignore: &[String]) -> Result<Value, String> {
+    let mut masked = value.clone();
+    for expression in ignore {
+        let path = JsonPath::parse(expression)
+            .map_err(|e| format!("invalid ignore path '{expression}': {e}"))?;
+        let pointers: Vec<String> = path
+            .query_located(value)
+            .locations()
+            .map(|location| location.to_json_pointer())
+            .collect();
+        for pointer in pointers {
+            if let Some(target) = masked.pointer_mut(&pointer) {
+                *target = Value::String(IGNORED_PLACEHOLDER.to_string());
+            }
+        }
+    }
+    Ok(masked)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn settings(name: &str) -> SnapshotSettings {
+        let dir = std::env::temp_dir().join(format!("httprun-snap-{name}-{}", std::process::id()));
+        SnapshotSettings::new(&dir.join("api.http"), false)
+    }
+
+    #[test]
+    fn records_then_compares_ignoring_volatile_fields() {
+        let settings = settings("cmp");
+        let ignore = vec!["$.createdAt".to_string(), "$.items[*].id".to_string()];
+
+        let first = json!({ "createdAt": "2024-01-01", "items": [{ "id": 1, "name": "a" }] });
+        let written = check(&settings, "list items", &first, &ignore);
+        assert!(written.passed && written.name.contains("written"), "{written:?}");
+        assert!(settings.dir.join("list_items.json").is_file());
+
+        let same = json!({ "createdAt": "2025-06-30", "items": [{ "id": 9, "name": "a" }] });
+        assert!(check(&settings, "list items", &same, &ignore).passed);
+
+        let changed = json!({ "createdAt": "2025-06-30", "items": [{ "id": 9, "name": "b" }] });
+        let failed = check(&settings, "list items", &changed, &ignore);
+        let message = failed.failure_message().unwrap();
+        assert!(message.contains("-       \"name\": \"a\""), "{message}");
+        assert!(message.contains("+       \"name\": \"b\""), "{message}");
+
+        let update = SnapshotSettings { update: true, ..settings.clone() };
+        assert!(check(&update, "list items", &changed, &ignore).passed);
+        assert!(check(&settings, "list items", &changed, &ignore).passed);
+
+        std::fs::remove_dir_all(settings.dir.parent().unwrap().parent().unwrap()).ok();
+    }
+}
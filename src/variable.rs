@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use regex::Regex;
 use std::sync::LazyLock;
@@ -8,7 +8,7 @@ use crate::error::AppError;
 static VARIABLE_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"\{\{([^}]+)\}\}").unwrap());
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct VariableStore {
     env_vars: HashMap<String, String>,
     global_vars: HashMap<String, String>,
@@ -28,7 +28,24 @@ impl VariableStore {
         &self.global_vars
     }
 
-    pub fn merge_globals(&mut self, globals: &HashMap<String, String>) {
+    /// The same variables with `globals` as the global ones.
+    pub fn with_globals(&self, globals: HashMap<String, String>) -> Self {
+        Self {
+            global_vars: globals,
+            ..self.clone()
+        }
+    }
+
+    /// Apply the globals produced by a handler: removals first, then the
+    /// handler's view of the remaining globals.
+    pub fn merge_globals(
+        &mut self,
+        globals: &HashMap<String, String>,
+        removed: &HashSet<String>,
+    ) {
+        for k in removed {
+            self.global_vars.remove(k);
+        }
         for (k, v) in globals {
             self.global_vars.insert(k.clone(), v.clone());
         }
@@ -38,6 +55,25 @@ impl VariableStore {
         self.in_place_vars.insert(name, value);
     }
 
+    /// Names of the `{{variables}}` in `input` that no in-place, global or
+    /// environment variable defines (dynamic `$` variables excluded).
+    pub fn undefined_in(&self, input: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for caps in VARIABLE_RE.captures_iter(input) {
+            let name = caps[1].trim();
+            if name.starts_with('$')
+                || self.in_place_vars.contains_key(name)
+                || self.global_vars.contains_key(name)
+                || self.env_vars.contains_key(name)
+                || names.iter().any(|n| n == name)
+            {
+                continue;
+            }
+            names.push(name.to_string());
+        }
+        names
+    }
+
     /// Substitute all {{variable}} references in the input string.
     /// Precedence: in_place_vars > global_vars > env_vars
     pub fn substitute(&self, input: &str) -> Result<String, AppError> {